@@ -1,8 +1,17 @@
 mod command_line_ui;
+mod demos_ui;
+mod extra_args_ui;
 mod game_engine_config_ui;
 mod game_profile_ui;
 mod input_files_config_ui;
 mod iwad_config_ui;
+mod multiplayer_ui;
+mod path_menu_ui;
+mod profile_data_ui;
+mod profile_info_ui;
+mod savegames_ui;
 mod wrappers_ui;
 
 pub use game_profile_ui::game_profile_ui;
+pub(crate) use input_files_config_ui::add_input_file_dialog;
+pub(crate) use profile_info_ui::RENAME_REQUEST_ID;
@@ -2,11 +2,16 @@ use arboard::Clipboard;
 use eframe::egui;
 use std::process::Command;
 
-pub(super) fn command_line_ui(ui: &mut egui::Ui, clipboard: &mut Clipboard, cmd: &Option<Command>) {
-    let cmd_str = cmd
-        .as_ref()
-        .map(|cmd| format!("{:?}", cmd))
-        .unwrap_or("<Incomplete command>".to_string());
+/// Shows the launched commands, one per line.
+pub(super) fn command_line_ui(ui: &mut egui::Ui, clipboard: &mut Clipboard, cmds: &[Command]) {
+    let cmd_str = if cmds.is_empty() {
+        "<Incomplete command>".to_string()
+    } else {
+        cmds.iter()
+            .map(|cmd| format!("{:?}", cmd))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
     ui.horizontal(|ui| {
         ui.label("Command line:");
         if ui.button("Copy").clicked() {
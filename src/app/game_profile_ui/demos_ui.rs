@@ -0,0 +1,214 @@
+use crate::app::library_ui::format_time_ago;
+use crate::config::{Config, RunCategory};
+use crate::demo::{
+    DemoAction, DemoGame, build_demo_cmd, demo_dir, demo_mismatches, list_demos, next_demo_path,
+    read_demo_header,
+};
+use crate::engine::{EngineFamily, is_dsda};
+use crate::paths::PathResolver;
+use eframe::egui;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::SystemTime;
+use tinyfiledialogs as tfd;
+
+const DEMO_LIST_HEIGHT: f32 = 120.0;
+
+/// Records demos into the profile's demo folder and plays back the ones in it.
+pub(super) fn demos_ui(
+    ui: &mut egui::Ui,
+    cfg: &mut Config,
+    cmds_to_launch: &mut Vec<Command>,
+    store_config: &mut bool,
+) {
+    let resolver = PathResolver::new(cfg);
+    let tab_config = cfg.get_active_tab();
+    let raw_dir = demo_dir(tab_config);
+    let dir = resolver.resolve(&raw_dir);
+    let family = tab_config.engine_path.as_deref().map(EngineFamily::of);
+    let game = DemoGame::from_iwad(tab_config.iwad_path.as_deref());
+    let selection_id = egui::Id::new(("demo_selection", tab_config.id));
+    let mut selected: Option<PathBuf> = ui.data(|d| d.get_temp(selection_id)).flatten();
+    let mut launch = None;
+    let mut warnings = Vec::new();
+
+    egui::CollapsingHeader::new("Demos").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Folder:");
+            ui.label(egui::RichText::new(&raw_dir).monospace())
+                .on_hover_text(dir.to_string_lossy());
+            if ui.button("...").clicked() {
+                let start_dir = dir.to_string_lossy();
+                if let Some(new_dir) = tfd::select_folder_dialog("Select Demo Folder", &start_dir) {
+                    cfg.get_active_tab_mut().demo_dir = Some(resolver.contract(new_dir));
+                    *store_config = true;
+                }
+            }
+            if cfg.get_active_tab().demo_dir.is_some() && ui.button("Reset").clicked() {
+                cfg.get_active_tab_mut().demo_dir = None;
+                *store_config = true;
+            }
+        });
+
+        if cfg
+            .get_active_tab()
+            .engine_path
+            .as_deref()
+            .is_some_and(is_dsda)
+        {
+            speedrun_ui(ui, cfg, store_config);
+        }
+
+        let tab_config = cfg.get_active_tab();
+        let next_demo = next_demo_path(tab_config, &dir);
+        ui.horizontal(|ui| {
+            let file_name = next_demo
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if ui
+                .add_enabled(family.is_some(), egui::Button::new("Record"))
+                .on_hover_text(format!("Record to {}", file_name))
+                .clicked()
+            {
+                launch = Some((DemoAction::Record, next_demo.clone()));
+            }
+            ui.weak(file_name);
+        });
+
+        let demos = list_demos(&dir);
+        if demos.is_empty() {
+            ui.weak("<No demos>");
+        } else {
+            egui::ScrollArea::vertical()
+                .max_height(DEMO_LIST_HEIGHT)
+                .show(ui, |ui| {
+                    for demo in &demos {
+                        let name = demo
+                            .path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let age = demo
+                            .modified
+                            .and_then(|m| SystemTime::now().duration_since(m).ok())
+                            .map(|age| format_time_ago(age.as_secs()))
+                            .unwrap_or_default();
+                        let is_selected = selected.as_ref() == Some(&demo.path);
+                        let header = read_demo_header(demo, game);
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(is_selected, name).clicked() {
+                                selected = Some(demo.path.clone());
+                            }
+                            ui.weak(age);
+                            match &header {
+                                Ok(header) => ui.weak(header.summary()),
+                                Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                            };
+                        });
+                        if is_selected && let Ok(header) = &header {
+                            warnings = demo_mismatches(header, cfg.get_active_tab());
+                        }
+                    }
+                });
+        }
+
+        if selected.as_ref().is_some_and(|s| !s.exists()) {
+            selected = None;
+        }
+        if selected.is_some() {
+            for warning in &warnings {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+            }
+        }
+        ui.horizontal(|ui| {
+            for action in [
+                DemoAction::Play,
+                DemoAction::Timedemo,
+                DemoAction::Fastdemo,
+                DemoAction::Continue,
+            ] {
+                let supported = family.is_some_and(|f| action.is_supported(f));
+                let response = ui.add_enabled(
+                    selected.is_some() && supported,
+                    egui::Button::new(action.name()),
+                );
+                let response = if supported {
+                    response
+                } else {
+                    response.on_disabled_hover_text("Not supported by this engine")
+                };
+                if response.clicked()
+                    && let Some(demo) = &selected
+                {
+                    launch = Some((action, demo.clone()));
+                }
+            }
+        });
+    });
+
+    ui.data_mut(|d| d.insert_temp(selection_id, selected));
+
+    if let Some((action, demo)) = launch {
+        // Engines don't create missing folders for recordings.
+        if matches!(action, DemoAction::Record | DemoAction::Continue)
+            && let Err(err) = fs::create_dir_all(&dir)
+        {
+            tfd::message_box_ok(
+                "Demos",
+                &format!("{}: {}", dir.display(), err),
+                tfd::MessageBoxIcon::Error,
+            );
+            return;
+        }
+        let confirmed = action == DemoAction::Record
+            || warnings.is_empty()
+            || tfd::message_box_yes_no(
+                "Demos",
+                &format!(
+                    "The demo may not match the profile:\n{}\n\nPlay it anyway?",
+                    warnings.join("\n")
+                ),
+                tfd::MessageBoxIcon::Warning,
+                tfd::YesNo::No,
+            ) == tfd::YesNo::Yes;
+        if confirmed {
+            *cmds_to_launch = build_demo_cmd(cfg, action, &demo).into_iter().collect();
+        }
+    }
+}
+
+/// dsda-doom speedrun mode: the run category, the DSDA submission text file and TAS mode. Runs are
+/// analyzed, and recordings renamed, when the engine exits.
+fn speedrun_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let speedrun = &mut cfg.get_active_tab_mut().speedrun;
+    ui.horizontal(|ui| {
+        *store_config |= ui
+            .checkbox(&mut speedrun.enabled, "Speedrun")
+            .on_hover_text("Analyze runs and name recordings following DSDA conventions")
+            .changed();
+        ui.add_enabled_ui(speedrun.enabled, |ui| {
+            egui::ComboBox::from_id_salt("speedrun_category")
+                .selected_text(speedrun.category.name())
+                .show_ui(ui, |ui| {
+                    for category in RunCategory::ALL {
+                        *store_config |= ui
+                            .selectable_value(&mut speedrun.category, category, category.name())
+                            .changed();
+                    }
+                });
+            *store_config |= ui
+                .checkbox(&mut speedrun.export_text_file, "Export text file")
+                .on_hover_text("Write the text file DSDA submissions need next to the demo")
+                .changed();
+            *store_config |= ui
+                .checkbox(&mut speedrun.tas, "TAS")
+                .on_hover_text(
+                    "Tool-assisted run: turn strict mode off to use build mode, key frames \
+                     and the other TAS tools",
+                )
+                .changed();
+        });
+    });
+}
@@ -0,0 +1,17 @@
+use crate::config::Config;
+use eframe::egui;
+
+pub(super) fn extra_args_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Extra arguments:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut cfg.get_active_tab_mut().extra_args)
+                .code_editor()
+                .hint_text("e.g. -skill 4 -warp 1")
+                .desired_width(f32::INFINITY),
+        );
+        if response.changed() {
+            *store_config = true;
+        }
+    });
+}
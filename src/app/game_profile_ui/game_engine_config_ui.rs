@@ -1,24 +1,59 @@
+use crate::app::game_profile_ui::path_menu_ui::path_menu_ui;
 use crate::config::Config;
+use crate::flatpak::{flatpak_app_id, installed_flatpak_engines};
+#[cfg(target_os = "linux")]
+use crate::flatpak::{flatpak_engine_path, refresh_installed_flatpak_engines};
+use crate::paths::PathResolver;
 use eframe::egui;
 use std::path::Path;
+use std::time::Duration;
 use tinyfiledialogs as tfd;
 
 const MIN_LABEL_WIDTH: f32 = 50.0;
 const CONFIGURE_BUTTON_WIDTH: f32 = 16.0;
+#[cfg(target_os = "linux")]
+const FLATPAK_BUTTON_WIDTH: f32 = 56.0;
+/// How often to check whether the Flatpak engines have been listed.
+const LISTING_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
 
 pub(super) fn game_engine_config_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let resolver = PathResolver::new(cfg);
     ui.horizontal(|ui| {
-        let tab_config = cfg.get_active_tab();
         ui.label("Game engine:");
-        allocate_truncated_label_ui(ui, CONFIGURE_BUTTON_WIDTH, |ui| {
-            ui.add(
-                egui::Label::new(
-                    egui::RichText::new(tab_config.engine_path.as_deref().unwrap_or("<Empty>"))
-                        .monospace(),
-                )
-                .truncate(),
-            )
+        #[cfg(target_os = "linux")]
+        let buttons_width =
+            CONFIGURE_BUTTON_WIDTH + FLATPAK_BUTTON_WIDTH + ui.spacing().item_spacing.x;
+        #[cfg(not(target_os = "linux"))]
+        let buttons_width = CONFIGURE_BUTTON_WIDTH;
+        allocate_truncated_label_ui(ui, buttons_width, |ui| {
+            let tab_config = cfg.get_active_tab_mut();
+            let mut text =
+                egui::RichText::new(tab_config.engine_path.as_deref().unwrap_or("<Empty>"))
+                    .monospace();
+            let missing = tab_config.engine_path.as_ref().is_some_and(|p| {
+                match flatpak_app_id(p) {
+                    // Not missing until the installed engines are known.
+                    Some(app_id) => match installed_flatpak_engines() {
+                        Some(engines) => !engines.iter().any(|a| a.id == app_id),
+                        None => {
+                            ui.ctx().request_repaint_after(LISTING_REPAINT_INTERVAL);
+                            false
+                        }
+                    },
+                    None => !resolver.resolve(p).exists(),
+                }
+            });
+            if missing {
+                text = text.color(ui.visuals().error_fg_color);
+            }
+            let response = ui.add(egui::Label::new(text).truncate());
+            if let Some(engine_path) = tab_config.engine_path.as_mut()
+                && path_menu_ui(response, engine_path, &resolver)
+            {
+                *store_config = true;
+            }
         });
+        let tab_config = cfg.get_active_tab();
         if ui
             .add_sized(
                 egui::vec2(CONFIGURE_BUTTON_WIDTH, ui.spacing().interact_size.y),
@@ -26,21 +61,58 @@ pub(super) fn game_engine_config_ui(ui: &mut egui::Ui, cfg: &mut Config, store_c
             )
             .clicked()
         {
-            let start_dir = tab_config
+            let engine_dir = tab_config
                 .engine_path
                 .as_ref()
-                .and_then(|p| Path::new(p).parent().map(|d| d.to_str().unwrap_or(".")))
+                .and_then(|p| resolver.resolve(p).parent().map(|d| d.to_path_buf()));
+            let start_dir = engine_dir
+                .as_deref()
+                .and_then(|d| d.to_str())
                 .or(cfg.last_engine_dir.as_deref())
                 .unwrap_or(".");
             let path = tfd::open_file_dialog("Select Game Engine", start_dir, None);
             if let Some(path) = path {
-                cfg.get_active_tab_mut().engine_path = Some(path.clone());
+                cfg.get_active_tab_mut().engine_path = Some(resolver.contract(path.clone()));
                 cfg.last_engine_dir = Path::new(&path)
                     .parent()
                     .map(|d| d.to_string_lossy().to_string());
                 *store_config = true;
             }
         }
+        #[cfg(target_os = "linux")]
+        flatpak_engine_menu_ui(ui, cfg, store_config);
+    });
+}
+
+/// Lists the Doom engines installed as Flatpaks to pick one as the engine.
+#[cfg(target_os = "linux")]
+fn flatpak_engine_menu_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    ui.menu_button("Flatpak", |ui| {
+        let Some(engines) = installed_flatpak_engines() else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Listing Flatpak engines…");
+            });
+            ui.ctx().request_repaint_after(LISTING_REPAINT_INTERVAL);
+            return;
+        };
+        if engines.is_empty() {
+            ui.label("<No Flatpak engines>");
+        }
+        for app in &engines {
+            if ui
+                .button(&app.name)
+                .on_hover_text(app.id.as_str())
+                .clicked()
+            {
+                cfg.get_active_tab_mut().engine_path = Some(flatpak_engine_path(&app.id));
+                *store_config = true;
+            }
+        }
+        ui.separator();
+        if ui.button("Refresh").clicked() {
+            refresh_installed_flatpak_engines();
+        }
     });
 }
 
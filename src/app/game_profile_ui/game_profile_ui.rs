@@ -1,14 +1,26 @@
 use crate::app::game_profile_ui::command_line_ui::command_line_ui;
+use crate::app::game_profile_ui::demos_ui::demos_ui;
+use crate::app::game_profile_ui::extra_args_ui::extra_args_ui;
 use crate::app::game_profile_ui::game_engine_config_ui::game_engine_config_ui;
 use crate::app::game_profile_ui::input_files_config_ui::input_files_config_ui;
 use crate::app::game_profile_ui::iwad_config_ui::iwad_config_ui;
+use crate::app::game_profile_ui::multiplayer_ui::multiplayer_ui;
+use crate::app::game_profile_ui::profile_data_ui::profile_data_ui;
+use crate::app::game_profile_ui::profile_info_ui::profile_info_ui;
+use crate::app::game_profile_ui::savegames_ui::savegames_ui;
 #[cfg(target_os = "linux")]
 use crate::app::game_profile_ui::wrappers_ui::wrappers_ui;
 use crate::config::Config;
+use crate::files::{FileKind, classify_file};
+use crate::launch::build_launch_cmds;
+use crate::paths::PathResolver;
+use crate::relink::{missing_paths, relink_missing_files};
 use arboard::Clipboard;
 use eframe::egui;
 use std::process::Command;
+use tinyfiledialogs as tfd;
 
+#[allow(clippy::too_many_arguments)]
 pub fn game_profile_ui(
     titlepic_texture: &Option<egui::TextureHandle>,
     clipboard: &mut Clipboard,
@@ -16,23 +28,123 @@ pub fn game_profile_ui(
     cfg: &mut Config,
     input_path_indexes_to_remove: &mut Vec<usize>,
     iwad_to_load: &mut Option<String>,
+    cmds_to_launch: &mut Vec<Command>,
     store_config: &mut bool,
 ) {
     egui::CentralPanel::default().show_inside(ui, |ui| {
         render_background(ui, titlepic_texture);
-        game_engine_config_ui(ui, cfg, store_config);
-        iwad_config_ui(ui, cfg, iwad_to_load, store_config);
-        input_files_config_ui(ui, cfg, input_path_indexes_to_remove, store_config);
-        #[cfg(target_os = "linux")]
-        wrappers_ui(ui, cfg, store_config);
-        let cmd = build_cmd(cfg);
-        if cfg.show_command_line {
-            command_line_ui(ui, clipboard, &cmd);
+        let dropped_inputs = apply_dropped_files(ui, cfg, iwad_to_load, store_config);
+        // The command line and Launch stay visible below the scrolled sections.
+        egui::Panel::bottom("launch_bar")
+            .frame(egui::Frame::NONE)
+            .show_inside(ui, |ui| {
+                let cmds = build_launch_cmds(cfg);
+                if cfg.show_command_line {
+                    command_line_ui(ui, clipboard, &cmds);
+                }
+                if ui
+                    .button("Launch")
+                    .on_hover_text(&cfg.keymap.launch)
+                    .clicked()
+                {
+                    *cmds_to_launch = cmds;
+                }
+            });
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            profile_info_ui(ui, cfg, store_config);
+            missing_files_ui(ui, cfg, store_config);
+            game_engine_config_ui(ui, cfg, store_config);
+            iwad_config_ui(ui, cfg, iwad_to_load, store_config);
+            input_files_config_ui(
+                ui,
+                cfg,
+                input_path_indexes_to_remove,
+                dropped_inputs,
+                store_config,
+            );
+            extra_args_ui(ui, cfg, store_config);
+            profile_data_ui(ui, cfg, store_config);
+            savegames_ui(ui, cfg, cmds_to_launch);
+            #[cfg(target_os = "linux")]
+            wrappers_ui(ui, cfg, store_config);
+            multiplayer_ui(ui, cfg, cmds_to_launch, store_config);
+            demos_ui(ui, cfg, cmds_to_launch, store_config);
+        });
+    });
+}
+
+/// Assigns files dropped onto the window: IWADs and engines go to their slots, other
+/// supported files are returned to be inserted into the input list.
+fn apply_dropped_files(
+    ui: &egui::Ui,
+    cfg: &mut Config,
+    iwad_to_load: &mut Option<String>,
+    store_config: &mut bool,
+) -> Vec<String> {
+    let dropped_files = ui.input(|i| i.raw.dropped_files.clone());
+    let resolver = PathResolver::new(cfg);
+    let mut dropped_inputs = Vec::new();
+    for path in dropped_files.into_iter().filter_map(|f| f.path) {
+        let Some(kind) = classify_file(&path) else {
+            continue;
+        };
+        let path_str = path.to_string_lossy().to_string();
+        let tab_config = cfg.get_active_tab_mut();
+        match kind {
+            FileKind::Iwad => {
+                tab_config.iwad_path = Some(resolver.contract(path_str.clone()));
+                *iwad_to_load = Some(path_str);
+                *store_config = true;
+            }
+            FileKind::Engine => {
+                tab_config.engine_path = Some(resolver.contract(path_str));
+                *store_config = true;
+            }
+            FileKind::Input => dropped_inputs.push(resolver.contract(path_str)),
         }
-        if ui.button("Launch").clicked() {
-            if let Some(mut cmd) = cmd {
-                println!("Launching game:\n{:?}\n", cmd);
-                cmd.spawn().unwrap();
+    }
+    dropped_inputs
+}
+
+/// Warns about missing profile files and offers relinking them from the library folders.
+fn missing_files_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let missing = missing_paths(cfg.get_active_tab(), &PathResolver::new(cfg));
+    if missing.is_empty() {
+        return;
+    }
+    ui.horizontal(|ui| {
+        ui.colored_label(
+            ui.visuals().error_fg_color,
+            format!("{} missing file(s)", missing.len()),
+        )
+        .on_hover_text(missing.join("\n"));
+        if ui
+            .button("Relink")
+            .on_hover_text("Search library folders for missing files of all profiles")
+            .clicked()
+        {
+            if cfg.library_dirs.is_empty() {
+                tfd::message_box_ok(
+                    "Relink missing files",
+                    "No library folders are configured. Add them in Settings.",
+                    tfd::MessageBoxIcon::Warning,
+                );
+                return;
+            }
+            let report = relink_missing_files(cfg);
+            let mut message = format!(
+                "Relinked {} file(s) in {} profile(s).",
+                report.relinked, report.profiles
+            );
+            if !report.still_missing.is_empty() {
+                message.push_str(&format!(
+                    "\n\nStill missing:\n{}",
+                    report.still_missing.join("\n")
+                ));
+            }
+            tfd::message_box_ok("Relink missing files", &message, tfd::MessageBoxIcon::Info);
+            if report.relinked > 0 {
+                *store_config = true;
             }
         }
     });
@@ -48,33 +160,3 @@ fn render_background(ui: &mut egui::Ui, titlepic_texture: &Option<egui::TextureH
         );
     }
 }
-
-fn build_cmd(cfg: &Config) -> Option<Command> {
-    let tab_config = cfg.get_active_tab();
-    if let (Some(engine), Some(iwad)) = (
-        tab_config.engine_path.as_ref(),
-        tab_config.iwad_path.as_ref(),
-    ) {
-        let mut cmd = if tab_config.use_umu_run {
-            let mut cmd = Command::new("umu-run");
-            cmd.env("PROTONPATH", &tab_config.proton_runner);
-            cmd.arg(engine);
-            cmd
-        } else {
-            Command::new(engine)
-        };
-
-        if tab_config.use_mangohud {
-            cmd.env("MANGOHUD", "1");
-        }
-
-        cmd.arg("-iwad")
-            .arg(iwad)
-            .arg("-file")
-            .args(&tab_config.input_paths);
-
-        Some(cmd)
-    } else {
-        None
-    }
-}
@@ -1,57 +1,69 @@
-use crate::config::Config;
+use crate::app::game_profile_ui::path_menu_ui::path_menu_ui;
+use crate::archive::{archive_check, archive_relative_path, expand_archive_inputs};
+use crate::config::{Config, TabConfig};
+use crate::paths::PathResolver;
 use eframe::egui;
 use std::path::Path;
+use std::time::Duration;
 use tinyfiledialogs as tfd;
 
 const CONFIGURE_BUTTON_WIDTH: f32 = 16.0;
 const PATH_COLOR_SAT: f32 = 0.5;
 const PATH_COLOR_VAL: f32 = 0.6;
+const INPUT_FILE_FILTER: (&[&str], &str) = (
+    &[
+        "*.WAD", "*.wad", "*.deh", "*.DEH", "*.bex", "*.BEX", "*.pk3", "*.PK3", "*.zip", "*.ZIP",
+        "*.7z", "*.7Z",
+    ],
+    "Supported files (*.wad, *.deh, *.bex, *.pk3, *.zip, *.7z)",
+);
 
 pub(super) fn input_files_config_ui(
     ui: &mut egui::Ui,
     cfg: &mut Config,
     input_path_indexes_to_remove: &mut Vec<usize>,
+    dropped_inputs: Vec<String>,
     store_config: &mut bool,
 ) {
     ui.horizontal(|ui| {
         ui.label("Input files:");
-        if ui.button("Add").clicked() {
-            let tab_config = cfg.get_active_tab();
-            let start_dir = tab_config
-                .last_input_dir
-                .as_deref()
-                .or_else(|| {
-                    tab_config.input_paths.last().and_then(|last| {
-                        Path::new(last).parent().map(|d| d.to_str().unwrap_or("."))
-                    })
-                })
-                .unwrap_or(".");
-            let path = tfd::open_file_dialog(
-                "Add Input File",
-                start_dir,
-                Some((
-                    &["*.WAD", "*.wad", "*.deh", "*.pk3"],
-                    "Supported files (*.WAD, *.wad, *.deh, *.pk3)",
-                )),
-            );
-            if let Some(path) = path {
-                let tab_config = cfg.get_active_tab_mut();
-                tab_config.input_paths.push(path.clone());
-                tab_config.last_input_dir = Path::new(&path)
-                    .parent()
-                    .map(|d| d.to_string_lossy().to_string());
-                *store_config = true;
-            }
+        if ui
+            .button("Add")
+            .on_hover_text(&cfg.keymap.add_input_file)
+            .clicked()
+            && add_input_file_dialog(cfg)
+        {
+            *store_config = true;
+        }
+        if ui.input(|i| !i.raw.hovered_files.is_empty()) {
+            ui.label(egui::RichText::new("Drop files to add them").strong());
         }
     });
-    ui.group(|ui| {
+    let resolver = PathResolver::new(cfg);
+    for archive in &cfg.get_active_tab().archives {
+        match archive_check(archive, &resolver) {
+            Some(Ok(())) => {}
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Extracting {}…", archive.path));
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            }
+        }
+    }
+    let mut row_rects = Vec::new();
+    let group_response = ui.group(|ui| {
         if cfg.get_active_tab().input_paths.is_empty() {
             ui.label("<Empty>");
         }
         let initial_len = cfg.get_active_tab().input_paths.len();
         for index in 0..initial_len {
             let path = cfg.get_active_tab().input_paths[index].clone();
-            ui.horizontal(|ui| {
+            let row = ui.horizontal(|ui| {
                 let tab_config = cfg.get_active_tab_mut();
                 if ui
                     .add_enabled(index > 0, egui::Button::new("/\\"))
@@ -77,23 +89,21 @@ pub(super) fn input_files_config_ui(
                     )
                     .clicked()
                 {
-                    let start_dir = Path::new(&path)
-                        .parent()
-                        .and_then(|d| d.to_str())
-                        .unwrap_or(".");
+                    let resolved = resolver.resolve(&path);
+                    let start_dir = resolved.parent().and_then(|d| d.to_str()).unwrap_or(".");
                     let sel = tfd::open_file_dialog(
                         "Replace Input File",
                         start_dir,
-                        Some((
-                            &["*.WAD", "*.wad", "*.deh", "*.pk3"],
-                            "Supported files (*.WAD, *.wad, *.deh, *.pk3)",
-                        )),
+                        Some(INPUT_FILE_FILTER),
                     );
                     if let Some(new_path) = sel {
-                        tab_config.input_paths[index] = new_path.clone();
                         tab_config.last_input_dir = Path::new(&new_path)
                             .parent()
                             .map(|d| d.to_string_lossy().to_string());
+                        let new_path = resolver.contract(new_path);
+                        let new_paths = expand_archives(tab_config, vec![new_path], &resolver);
+                        tab_config.input_paths.splice(index..=index, new_paths);
+                        tab_config.prune_archives();
                         *store_config = true;
                     }
                 }
@@ -108,17 +118,101 @@ pub(super) fn input_files_config_ui(
                     input_path_indexes_to_remove.push(index);
                 }
 
-                ui.add(
-                    egui::Label::new(
-                        egui::RichText::new(path.clone())
-                            .monospace()
-                            .color(color_for_path(&path)),
-                    )
-                    .truncate(),
+                // Files extracted from an archive are shown relative to the archive.
+                let archive = tab_config.archive_for_input(&path);
+                let (label, color_key) = match archive {
+                    Some(archive) => {
+                        let relative =
+                            archive_relative_path(&path, &archive.hash).unwrap_or_default();
+                        (
+                            format!("{} › {}", archive.path, relative),
+                            archive.path.clone(),
+                        )
+                    }
+                    None => (path.clone(), path.clone()),
+                };
+                let color = if resolver.resolve(&path).exists() {
+                    color_for_path(&color_key)
+                } else {
+                    ui.visuals().error_fg_color
+                };
+                let response = ui.add(
+                    egui::Label::new(egui::RichText::new(label).monospace().color(color))
+                        .truncate(),
                 );
+                if archive.is_some() {
+                    // Extracted files are managed by the launcher, only their archive is editable.
+                    response.on_hover_text(resolver.resolve_string(&path));
+                } else if path_menu_ui(response, &mut tab_config.input_paths[index], &resolver) {
+                    *store_config = true;
+                }
             });
+            row_rects.push(row.response.rect);
         }
     });
+
+    if !dropped_inputs.is_empty() {
+        // Insert before the first row below the drop position, or append if dropped elsewhere.
+        let drop_pos = ui.input(|i| i.pointer.latest_pos());
+        let tab_config = cfg.get_active_tab_mut();
+        let dropped_inputs = expand_archives(tab_config, dropped_inputs, &resolver);
+        let index = drop_pos
+            .filter(|pos| group_response.response.rect.contains(*pos))
+            .map(|pos| row_rects.iter().filter(|r| r.center().y < pos.y).count())
+            .unwrap_or(tab_config.input_paths.len())
+            .min(tab_config.input_paths.len());
+        tab_config.input_paths.splice(index..index, dropped_inputs);
+        *store_config = true;
+    }
+}
+
+/// Asks for input files and appends them to the active profile. Returns true if files were added.
+pub(crate) fn add_input_file_dialog(cfg: &mut Config) -> bool {
+    let resolver = PathResolver::new(cfg);
+    let tab_config = cfg.get_active_tab();
+    let last_input_dir = tab_config
+        .input_paths
+        .last()
+        .and_then(|last| resolver.resolve(last).parent().map(|d| d.to_path_buf()));
+    let start_dir = tab_config
+        .last_input_dir
+        .as_deref()
+        .or_else(|| last_input_dir.as_deref().map(|d| d.to_str().unwrap_or(".")))
+        .unwrap_or(".");
+    let paths = tfd::open_file_dialog_multi("Add Input Files", start_dir, Some(INPUT_FILE_FILTER));
+    match paths {
+        Some(paths) if !paths.is_empty() => {
+            let tab_config = cfg.get_active_tab_mut();
+            tab_config.last_input_dir = paths.last().and_then(|path| {
+                Path::new(path)
+                    .parent()
+                    .map(|d| d.to_string_lossy().to_string())
+            });
+            let paths = paths.into_iter().map(|p| resolver.contract(p)).collect();
+            let paths = expand_archives(tab_config, paths, &resolver);
+            tab_config.input_paths.extend(paths);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Replaces archives among the paths with their extracted playable files. Archives that
+/// fail to extract are reported and skipped.
+fn expand_archives(
+    tab_config: &mut TabConfig,
+    paths: Vec<String>,
+    resolver: &PathResolver,
+) -> Vec<String> {
+    let (expanded, errors) = expand_archive_inputs(tab_config, paths, resolver);
+    if !errors.is_empty() {
+        tfd::message_box_ok(
+            "Failed to add archive",
+            &errors.join("\n"),
+            tfd::MessageBoxIcon::Error,
+        );
+    }
+    expanded
 }
 
 fn hsv_to_rgb_u8(h_deg: f32, s: f32, v: f32) -> (u8, u8, u8) {
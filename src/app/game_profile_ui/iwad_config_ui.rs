@@ -1,4 +1,6 @@
+use crate::app::game_profile_ui::path_menu_ui::path_menu_ui;
 use crate::config::Config;
+use crate::paths::PathResolver;
 use eframe::egui;
 use std::path::Path;
 use tinyfiledialogs as tfd;
@@ -12,21 +14,26 @@ pub(super) fn iwad_config_ui(
     iwad_to_load: &mut Option<String>,
     store_config: &mut bool,
 ) {
+    let resolver = PathResolver::new(cfg);
     ui.horizontal(|ui| {
         ui.label("IWAD:");
         allocate_truncated_label_ui(ui, CONFIGURE_BUTTON_WIDTH, |ui| {
-            ui.add(
-                egui::Label::new(
-                    egui::RichText::new(
-                        cfg.get_active_tab()
-                            .iwad_path
-                            .as_deref()
-                            .unwrap_or("<Empty>"),
-                    )
-                    .monospace(),
-                )
-                .truncate(),
-            )
+            let iwad_path = &mut cfg.get_active_tab_mut().iwad_path;
+            let mut text =
+                egui::RichText::new(iwad_path.as_deref().unwrap_or("<Empty>")).monospace();
+            if iwad_path
+                .as_ref()
+                .is_some_and(|p| !resolver.resolve(p).exists())
+            {
+                text = text.color(ui.visuals().error_fg_color);
+            }
+            let response = ui.add(egui::Label::new(text).truncate());
+            if let Some(path) = iwad_path.as_mut()
+                && path_menu_ui(response, path, &resolver)
+            {
+                *iwad_to_load = Some(resolver.resolve_string(path));
+                *store_config = true;
+            }
         });
         if ui
             .add_sized(
@@ -35,11 +42,14 @@ pub(super) fn iwad_config_ui(
             )
             .clicked()
         {
-            let start_dir = cfg
+            let iwad_dir = cfg
                 .get_active_tab()
                 .iwad_path
                 .as_ref()
-                .and_then(|p| Path::new(p).parent().map(|d| d.to_str().unwrap_or(".")))
+                .and_then(|p| resolver.resolve(p).parent().map(|d| d.to_path_buf()));
+            let start_dir = iwad_dir
+                .as_deref()
+                .and_then(|d| d.to_str())
                 .or(cfg.last_iwad_dir.as_deref())
                 .unwrap_or(".");
             let path = tfd::open_file_dialog(
@@ -48,7 +58,7 @@ pub(super) fn iwad_config_ui(
                 Some((&["*.WAD", "*.wad"], "WAD files (*.WAD, *.wad)")),
             );
             if let Some(path) = path {
-                cfg.get_active_tab_mut().iwad_path = Some(path.clone());
+                cfg.get_active_tab_mut().iwad_path = Some(resolver.contract(path.clone()));
                 cfg.last_iwad_dir = Path::new(&path)
                     .parent()
                     .map(|d| d.to_string_lossy().to_string());
@@ -0,0 +1,148 @@
+use crate::config::{Config, NetGameMode, NetRole};
+use crate::multiplayer::{NetEngine, build_multiplayer_cmds};
+use eframe::egui;
+use std::process::Command;
+
+/// Netplay settings used by Launch, and a local test game starting every player on this
+/// computer.
+pub(super) fn multiplayer_ui(
+    ui: &mut egui::Ui,
+    cfg: &mut Config,
+    cmds_to_launch: &mut Vec<Command>,
+    store_config: &mut bool,
+) {
+    let engine = cfg
+        .get_active_tab()
+        .engine_path
+        .as_deref()
+        .and_then(NetEngine::of);
+    let mut test_players = None;
+
+    egui::CollapsingHeader::new("Multiplayer").show(ui, |ui| {
+        let Some(engine) = engine else {
+            let color = if cfg.get_active_tab().multiplayer.enabled {
+                ui.visuals().error_fg_color
+            } else {
+                ui.visuals().weak_text_color()
+            };
+            ui.colored_label(color, "Not supported by this engine");
+            if cfg.get_active_tab().multiplayer.enabled && ui.button("Disable").clicked() {
+                cfg.get_active_tab_mut().multiplayer.enabled = false;
+                *store_config = true;
+            }
+            return;
+        };
+        let settings = &mut cfg.get_active_tab_mut().multiplayer;
+        let mut changed = false;
+        // The engine may have been changed to one supporting fewer players.
+        let max_players = engine.max_players();
+        if settings.players > max_players {
+            settings.players = max_players;
+            changed = true;
+        }
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut settings.enabled, "Launch online")
+                .on_hover_text("Host or join a game instead of playing alone")
+                .changed();
+            for role in NetRole::ALL {
+                changed |= ui
+                    .radio_value(&mut settings.role, role, role.name())
+                    .changed();
+            }
+        });
+
+        egui::Grid::new("multiplayer_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                if settings.role == NetRole::Join {
+                    ui.label("Address:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut settings.address)
+                                .hint_text("host or host:port"),
+                        )
+                        .changed();
+                    ui.end_row();
+                }
+                ui.label("Port:");
+                let default_port = engine.default_port();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.port)
+                            .custom_formatter(move |port, _| match port as u16 {
+                                0 => format!("Default ({})", default_port),
+                                port => port.to_string(),
+                            })
+                            .custom_parser(|text| text.trim().parse::<u16>().ok().map(f64::from)),
+                    )
+                    .changed();
+                ui.end_row();
+                if settings.role == NetRole::Host {
+                    ui.label("Players:");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut settings.players).range(2..=max_players))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Mode:");
+                    egui::ComboBox::from_id_salt("multiplayer_mode")
+                        .selected_text(settings.mode.name())
+                        .show_ui(ui, |ui| {
+                            for mode in NetGameMode::ALL {
+                                changed |= ui
+                                    .selectable_value(&mut settings.mode, mode, mode.name())
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Time limit:");
+                    changed |= limit_ui(
+                        ui,
+                        &mut settings.timelimit,
+                        " min",
+                        engine.supports_timelimit(),
+                    );
+                    ui.end_row();
+
+                    ui.label("Frag limit:");
+                    changed |=
+                        limit_ui(ui, &mut settings.fraglimit, "", engine.supports_fraglimit());
+                    ui.end_row();
+                }
+            });
+        *store_config |= changed;
+
+        let players = settings.players;
+        if ui
+            .button(format!("Test with {} players", players))
+            .on_hover_text("Host a game and start every player on this computer")
+            .clicked()
+        {
+            test_players = Some(players);
+        }
+    });
+
+    if let Some(players) = test_players {
+        *cmds_to_launch = build_multiplayer_cmds(cfg, Some(players));
+    }
+}
+
+/// Edits a time or frag limit, 0 meaning none.
+fn limit_ui(ui: &mut egui::Ui, value: &mut u32, suffix: &str, supported: bool) -> bool {
+    let suffix = suffix.to_string();
+    let response = ui.add_enabled(
+        supported,
+        egui::DragValue::new(value).custom_formatter(move |value, _| match value as u32 {
+            0 => "None".to_string(),
+            value => format!("{}{}", value, suffix),
+        }),
+    );
+    let response = if supported {
+        response
+    } else {
+        response.on_disabled_hover_text("Not supported by this engine")
+    };
+    response.changed()
+}
@@ -0,0 +1,29 @@
+use crate::paths::PathResolver;
+use eframe::egui;
+
+/// Shows the resolved path on hover when it differs from the raw one, and offers editing
+/// the raw path from the context menu, e.g. to use path variables. Returns true if edited.
+pub(super) fn path_menu_ui(
+    response: egui::Response,
+    path: &mut String,
+    resolver: &PathResolver,
+) -> bool {
+    let resolved = resolver.resolve_string(path);
+    let response = if resolved != *path {
+        response.on_hover_text(format!("{}\n→ {}", path, resolved))
+    } else {
+        response.on_hover_text(path.as_str())
+    };
+    let mut edited = false;
+    response.context_menu(|ui| {
+        ui.label("Path (supports $VARIABLES and relative paths):");
+        edited = ui
+            .add(
+                egui::TextEdit::singleline(path)
+                    .code_editor()
+                    .desired_width(400.0),
+            )
+            .changed();
+    });
+    edited
+}
@@ -0,0 +1,66 @@
+use crate::config::Config;
+use crate::engine::EngineFamily;
+use crate::paths::PathResolver;
+use crate::profile_data::profile_data_dir;
+use eframe::egui;
+use tinyfiledialogs as tfd;
+
+/// Keeps saves, screenshots and engine settings in a folder of the profile, so profiles
+/// don't overwrite each other's.
+pub(super) fn profile_data_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let resolver = PathResolver::new(cfg);
+    let tab_config = cfg.get_active_tab();
+    let raw_dir = profile_data_dir(tab_config);
+    let dir = resolver.resolve(&raw_dir);
+    let family = tab_config.engine_path.as_deref().map(EngineFamily::of);
+
+    egui::CollapsingHeader::new("Saves and settings").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Folder:");
+            ui.label(egui::RichText::new(&raw_dir).monospace())
+                .on_hover_text(dir.to_string_lossy());
+            if ui.button("...").clicked() {
+                let start_dir = dir.to_string_lossy();
+                if let Some(new_dir) =
+                    tfd::select_folder_dialog("Select Profile Data Folder", &start_dir)
+                {
+                    cfg.get_active_tab_mut().isolation.dir = Some(resolver.contract(new_dir));
+                    *store_config = true;
+                }
+            }
+            if cfg.get_active_tab().isolation.dir.is_some() && ui.button("Reset").clicked() {
+                cfg.get_active_tab_mut().isolation.dir = None;
+                *store_config = true;
+            }
+        });
+
+        let isolation = &mut cfg.get_active_tab_mut().isolation;
+        ui.horizontal(|ui| {
+            for (value, label, supported) in [
+                (
+                    &mut isolation.saves,
+                    "Separate saves",
+                    family.is_some_and(|f| f.savedir_arg().is_some()),
+                ),
+                (
+                    &mut isolation.screenshots,
+                    "Separate screenshots",
+                    family.is_some_and(|f| f.shotdir_arg().is_some()),
+                ),
+                (
+                    &mut isolation.config,
+                    "Separate engine settings",
+                    family.is_some_and(|f| !f.config_args().is_empty()),
+                ),
+            ] {
+                let response = ui.add_enabled(supported, egui::Checkbox::new(value, label));
+                let response = if supported {
+                    response
+                } else {
+                    response.on_disabled_hover_text("Not supported by this engine")
+                };
+                *store_config |= response.changed();
+            }
+        });
+    });
+}
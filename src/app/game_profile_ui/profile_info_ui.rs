@@ -0,0 +1,69 @@
+use crate::config::Config;
+use eframe::egui;
+
+const TAG_INPUT_WIDTH: f32 = 80.0;
+/// Temp data flag set by the rename shortcut to focus the name field.
+pub(crate) const RENAME_REQUEST_ID: &str = "rename_profile_request";
+
+pub(super) fn profile_info_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let tab_config = cfg.get_active_tab_mut();
+
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        let mut name = tab_config.name.clone().unwrap_or_default();
+        let response = ui.add(egui::TextEdit::singleline(&mut name).hint_text("<Automatic>"));
+        if ui
+            .data_mut(|d| d.remove_temp::<bool>(egui::Id::new(RENAME_REQUEST_ID)))
+            .unwrap_or_default()
+        {
+            response.request_focus();
+        }
+        if response.changed() {
+            tab_config.name = if name.trim().is_empty() {
+                None
+            } else {
+                Some(name)
+            };
+            *store_config = true;
+        }
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Tags:");
+        let mut tag_to_remove = None;
+        for (index, tag) in tab_config.tags.iter().enumerate() {
+            if ui
+                .button(format!("{} ×", tag))
+                .on_hover_text("Remove tag")
+                .clicked()
+            {
+                tag_to_remove = Some(index);
+            }
+        }
+        if let Some(index) = tag_to_remove {
+            tab_config.tags.remove(index);
+            *store_config = true;
+        }
+
+        // The new tag text is kept in egui memory until it is committed.
+        let input_id = ui.id().with(("new_tag", tab_config.id));
+        let mut new_tag: String = ui.data_mut(|d| d.get_temp(input_id).unwrap_or_default());
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut new_tag)
+                .hint_text("New tag")
+                .desired_width(TAG_INPUT_WIDTH),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if (ui.button("+").on_hover_text("Add tag").clicked() || submitted)
+            && !new_tag.trim().is_empty()
+        {
+            let tag = new_tag.trim().to_owned();
+            if !tab_config.tags.contains(&tag) {
+                tab_config.tags.push(tag);
+                *store_config = true;
+            }
+            new_tag.clear();
+        }
+        ui.data_mut(|d| d.insert_temp(input_id, new_tag));
+    });
+}
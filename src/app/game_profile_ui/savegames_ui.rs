@@ -0,0 +1,110 @@
+use crate::app::library_ui::format_time_ago;
+use crate::config::Config;
+use crate::demo::DemoGame;
+use crate::engine::EngineFamily;
+use crate::paths::PathResolver;
+use crate::profile_data::{profile_data_dir, profile_saves_dir};
+use crate::savegame::{SaveGame, Thumbnail, build_loadgame_cmd, list_savegames, read_save_info};
+use eframe::egui;
+use std::process::Command;
+use std::time::SystemTime;
+
+const SAVE_LIST_HEIGHT: f32 = 180.0;
+const THUMBNAIL_WIDTH: f32 = 64.0;
+
+/// Lists the savegames of the profile's save folder and launches into them.
+pub(super) fn savegames_ui(ui: &mut egui::Ui, cfg: &Config, cmds_to_launch: &mut Vec<Command>) {
+    let tab_config = cfg.get_active_tab();
+    let family = tab_config.engine_path.as_deref().map(EngineFamily::of);
+    let game = DemoGame::from_iwad(tab_config.iwad_path.as_deref());
+
+    egui::CollapsingHeader::new("Savegames").show(ui, |ui| {
+        if !tab_config.isolation.saves {
+            ui.weak("Enable separate saves to browse the savegames of this profile");
+            return;
+        }
+        let dir = PathResolver::new(cfg).resolve(&profile_data_dir(tab_config));
+        let saves = list_savegames(&profile_saves_dir(&dir));
+        if saves.is_empty() {
+            ui.weak("<No savegames>");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .max_height(SAVE_LIST_HEIGHT)
+            .show(ui, |ui| {
+                for save in &saves {
+                    ui.horizontal(|ui| {
+                        let can_load = save.slot.is_some() || family == Some(EngineFamily::ZDoom);
+                        if ui
+                            .add_enabled(can_load, egui::Button::new("Load"))
+                            .on_disabled_hover_text("The file name has no slot number")
+                            .clicked()
+                        {
+                            *cmds_to_launch = build_loadgame_cmd(cfg, save).into_iter().collect();
+                        }
+                        save_row_ui(ui, save, game);
+                    });
+                }
+            });
+    });
+}
+
+/// Shows a savegame's thumbnail, description and details.
+fn save_row_ui(ui: &mut egui::Ui, save: &SaveGame, game: DemoGame) {
+    let file_name = save
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let info = match read_save_info(save, game) {
+        Ok(info) => info,
+        Err(err) => {
+            ui.label(file_name);
+            ui.colored_label(ui.visuals().error_fg_color, err);
+            return;
+        }
+    };
+    if let Some(thumbnail) = &info.thumbnail {
+        let texture = thumbnail_texture(ui, save, thumbnail);
+        let [width, height] = thumbnail.size;
+        let size = egui::vec2(
+            THUMBNAIL_WIDTH,
+            THUMBNAIL_WIDTH * height as f32 / width.max(1) as f32,
+        );
+        ui.add(egui::Image::new((texture.id(), size)));
+    }
+    ui.vertical(|ui| {
+        let description = if info.description.is_empty() {
+            "<No description>"
+        } else {
+            info.description.as_str()
+        };
+        ui.strong(description).on_hover_text(&file_name);
+        let mut details = Vec::new();
+        if let Some(slot) = save.slot {
+            details.push(format!("Slot {}", slot));
+        }
+        details.extend(info.map.clone());
+        details.push(info.format.clone());
+        let age = save
+            .modified
+            .and_then(|m| SystemTime::now().duration_since(m).ok())
+            .map(|age| format_time_ago(age.as_secs()));
+        details.extend(info.time.clone().or(age));
+        ui.weak(details.join(" · "));
+    });
+}
+
+/// Uploads a thumbnail once per savegame version, keeping the texture in the UI memory.
+fn thumbnail_texture(ui: &egui::Ui, save: &SaveGame, thumbnail: &Thumbnail) -> egui::TextureHandle {
+    let id = egui::Id::new(("savepic", &save.path, save.modified));
+    if let Some(texture) = ui.data(|d| d.get_temp::<egui::TextureHandle>(id)) {
+        return texture;
+    }
+    let image = egui::ColorImage::from_rgba_unmultiplied(thumbnail.size, &thumbnail.rgba);
+    let texture = ui
+        .ctx()
+        .load_texture(format!("savepic-{:?}", id), image, Default::default());
+    ui.data_mut(|d| d.insert_temp(id, texture.clone()));
+    texture
+}
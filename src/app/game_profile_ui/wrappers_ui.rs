@@ -1,146 +1,531 @@
-use crate::config::Config;
+use crate::config::{Config, Wrapper};
+use crate::launch::{
+    GamescopeOptions, GamescopeUpscaler, UMU_PROTON_VERBS, UMU_STORES, WRAPPER_PRESETS,
+    engine_prefix, env_var, join_args, parse_env, profile_prefix, set_env_var, split_args,
+};
+use crate::paths::PathResolver;
+use crate::proton::{installed_proton_runners, refresh_installed_proton_runners};
 use eframe::egui;
 use std::fs;
-use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::path::Path;
+use tinyfiledialogs as tfd;
 
-static PROTON_RUNNERS: OnceLock<Mutex<Vec<ProtonRunner>>> = OnceLock::new();
-
-struct ProtonRunner {
-    name: String,
-    path: String,
+enum WrapperAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
 }
 
 pub(super) fn wrappers_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let resolver = PathResolver::new(cfg);
+    let proton_dirs = cfg.proton_dirs.clone();
     let tab_config = cfg.get_active_tab_mut();
-
-    let proton_runners = get_installed_proton_runners_cache();
-
-    if proton_runners.is_empty() && !tab_config.proton_runner.is_empty() {
-        tab_config.proton_runner = String::new();
-    }
+    let profile_prefix = profile_prefix(tab_config.id);
+    let engine_prefix = tab_config.engine_path.as_deref().map(engine_prefix);
 
     ui.label("Wrappers:");
 
     ui.group(|ui| {
-        ui.horizontal(|ui| {
-            if ui
-                .checkbox(&mut tab_config.use_umu_run, "umu-run")
-                .changed()
-            {
-                *store_config = true;
-            }
+        let mut action = None;
+        let count = tab_config.wrappers.len();
 
-            if proton_runners.is_empty() {
-                ui.add_enabled_ui(false, |ui| {
-                    egui::ComboBox::from_label("")
-                        .selected_text("<No Proton runners>")
-                        .show_ui(ui, |_| {});
-                });
-            } else {
-                let mut selected_index = proton_runners
-                    .iter()
-                    .position(|r| *r.path == tab_config.proton_runner)
-                    .unwrap_or_default();
+        if count == 0 {
+            ui.weak("<No wrappers>");
+        }
 
-                let selected_before = selected_index;
+        for (index, wrapper) in tab_config.wrappers.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    let mut changed = ui.checkbox(&mut wrapper.enabled, "").changed();
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut wrapper.executable)
+                                .hint_text("Executable")
+                                .desired_width(120.0),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut wrapper.args)
+                                .hint_text("Arguments")
+                                .desired_width(200.0),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut wrapper.env)
+                                .hint_text("NAME=value")
+                                .desired_width(160.0),
+                        )
+                        .on_hover_text("Environment variables")
+                        .changed();
+                    if changed {
+                        *store_config = true;
+                    }
 
-                egui::ComboBox::from_label("")
-                    .selected_text(proton_runners[selected_index].name.as_str())
-                    .show_ui(ui, |ui| {
-                        for (index, option) in proton_runners.iter().enumerate() {
-                            ui.selectable_value(&mut selected_index, index, option.name.as_str());
-                        }
-                    });
+                    if ui.add_enabled(index > 0, egui::Button::new("⏶")).clicked() {
+                        action = Some(WrapperAction::MoveUp(index));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("⏷"))
+                        .clicked()
+                    {
+                        action = Some(WrapperAction::MoveDown(index));
+                    }
+                    if ui.button("×").clicked() {
+                        action = Some(WrapperAction::Remove(index));
+                    }
+                });
 
-                if selected_index != selected_before || tab_config.proton_runner.is_empty() {
-                    tab_config.proton_runner = proton_runners
-                        .get(selected_index)
-                        .map(|runner| runner.path.clone())
-                        .unwrap_or_default();
-                    *store_config = true;
+                if wrapper.enabled && wrapper.is_wine() {
+                    wine_ui(ui, wrapper, &resolver, store_config);
+                } else if wrapper.enabled {
+                    match wrapper.program_name() {
+                        "umu-run" => umu_run_ui(
+                            ui,
+                            &mut wrapper.env,
+                            &profile_prefix,
+                            engine_prefix.as_deref(),
+                            &proton_dirs,
+                            &resolver,
+                            store_config,
+                        ),
+                        "gamescope" => gamescope_ui(ui, &mut wrapper.args, store_config),
+                        _ => {}
+                    }
                 }
-            }
-
-            if ui.button("Refresh").clicked() {
-                refresh_installed_proton_runners_cache();
+            });
+        }
 
-                let proton_runners = get_installed_proton_runners_cache();
+        match action {
+            Some(WrapperAction::MoveUp(index)) => tab_config.wrappers.swap(index - 1, index),
+            Some(WrapperAction::MoveDown(index)) => tab_config.wrappers.swap(index, index + 1),
+            Some(WrapperAction::Remove(index)) => {
+                tab_config.wrappers.remove(index);
+            }
+            None => {}
+        }
+        if action.is_some() {
+            *store_config = true;
+        }
 
-                if proton_runners
-                    .iter()
-                    .find(|r| &*r.path == &tab_config.proton_runner)
-                    .is_none()
-                {
-                    tab_config.proton_runner = String::new();
+        ui.menu_button("Add wrapper", |ui| {
+            for (name, executable, args, env) in WRAPPER_PRESETS {
+                if ui.button(name).clicked() {
+                    let mut wrapper = Wrapper::from_preset(executable, args, env);
+                    // New umu-run wrappers get a prefix of their own.
+                    if wrapper.program_name() == "umu-run" {
+                        set_env_var(&mut wrapper.env, "WINEPREFIX", Some(&profile_prefix));
+                    }
+                    tab_config.wrappers.push(wrapper);
+                    *store_config = true;
                 }
             }
         });
-
-        if ui
-            .checkbox(&mut tab_config.use_mangohud, "mangohud")
-            .changed()
-        {
-            *store_config = true;
-        }
     });
 }
 
-fn get_installed_proton_runners_cache() -> MutexGuard<'static, Vec<ProtonRunner>> {
-    PROTON_RUNNERS
-        .get_or_init(|| std::sync::Mutex::new(find_installed_proton_runners()))
-        .lock()
-        .unwrap()
+/// Where a profile's umu-run Wine prefix lives, derived from its `WINEPREFIX`.
+#[derive(Clone, Copy, PartialEq)]
+enum PrefixMode {
+    /// umu-run's own default, shared by all profiles without a prefix.
+    Default,
+    Profile,
+    Engine,
+    Custom,
 }
 
-fn refresh_installed_proton_runners_cache() {
-    let cache = PROTON_RUNNERS.get_or_init(|| Mutex::new(Vec::new()));
-    if let Ok(mut options) = cache.lock() {
-        *options = find_installed_proton_runners();
+fn prefix_mode_name(mode: PrefixMode) -> &'static str {
+    match mode {
+        PrefixMode::Default => "Shared (umu default)",
+        PrefixMode::Profile => "Per profile",
+        PrefixMode::Engine => "Per engine",
+        PrefixMode::Custom => "Custom",
     }
 }
 
-fn find_installed_proton_runners() -> Vec<ProtonRunner> {
-    let home = std::env::var("HOME").unwrap_or_default();
+/// Edits the umu-run variables: the Proton build (`PROTONPATH`), the prefix
+/// (`WINEPREFIX`), and `GAMEID`/`STORE`/`PROTON_VERB`, which select protonfixes and how
+/// the game is run.
+fn umu_run_ui(
+    ui: &mut egui::Ui,
+    env: &mut String,
+    profile_prefix: &str,
+    engine_prefix: Option<&str>,
+    proton_dirs: &[String],
+    resolver: &PathResolver,
+    store_config: &mut bool,
+) {
+    let env_before = env.clone();
+    let proton_runner = env_var(env, "PROTONPATH").unwrap_or_default();
+    let mut prefix = env_var(env, "WINEPREFIX").unwrap_or_default();
+    let mut game_id = env_var(env, "GAMEID").unwrap_or_default();
+    let mut store = env_var(env, "STORE").unwrap_or_default();
+    let mut verb = env_var(env, "PROTON_VERB").unwrap_or_default();
 
-    if home.is_empty() {
-        return Vec::new();
-    }
+    let mut prefix_mode = if prefix.is_empty() {
+        PrefixMode::Default
+    } else if prefix == profile_prefix {
+        PrefixMode::Profile
+    } else if Some(prefix.as_str()) == engine_prefix {
+        PrefixMode::Engine
+    } else {
+        PrefixMode::Custom
+    };
+    let prefix_mode_before = prefix_mode;
 
-    let mut options = Vec::new();
+    let mut new_proton_runner = None;
+    let mut refresh = false;
 
-    let compatibilitytools_dir = format!("{home}/.steam/steam/compatibilitytools.d");
+    ui.indent("umu-run", |ui| {
+        egui::Grid::new("umu_run_options").show(ui, |ui| {
+            ui.label("Proton:");
+            ui.horizontal(|ui| {
+                let proton_runners = installed_proton_runners(proton_dirs);
 
-    if let Ok(entries) = fs::read_dir(compatibilitytools_dir) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                if let Some(name) = entry.file_name().to_str() {
-                    options.push(ProtonRunner {
-                        name: name.to_string(),
-                        path: entry.path().to_str().unwrap_or_default().to_owned(),
+                if proton_runners.is_empty() {
+                    if !proton_runner.is_empty() {
+                        new_proton_runner = Some(String::new());
+                    }
+                    ui.add_enabled_ui(false, |ui| {
+                        egui::ComboBox::from_id_salt("proton_runner")
+                            .selected_text("<No Proton runners>")
+                            .show_ui(ui, |_| {});
                     });
+                } else {
+                    let mut selected_index = proton_runners
+                        .iter()
+                        .position(|r| r.path == proton_runner)
+                        .unwrap_or_default();
+
+                    let selected_before = selected_index;
+
+                    egui::ComboBox::from_id_salt("proton_runner")
+                        .selected_text(proton_runners[selected_index].name.as_str())
+                        .show_ui(ui, |ui| {
+                            for (index, option) in proton_runners.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut selected_index,
+                                    index,
+                                    format!("{} ({})", option.name, option.source),
+                                )
+                                .on_hover_text(&option.path);
+                            }
+                        });
+
+                    if selected_index != selected_before || proton_runner.is_empty() {
+                        new_proton_runner = Some(proton_runners[selected_index].path.clone());
+                    }
                 }
-            }
+
+                if ui.button("Refresh").clicked() {
+                    refresh = true;
+                }
+            });
+            ui.end_row();
+
+            ui.label("Prefix:");
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("umu_prefix_mode")
+                    .selected_text(prefix_mode_name(prefix_mode))
+                    .show_ui(ui, |ui| {
+                        for mode in [
+                            PrefixMode::Default,
+                            PrefixMode::Profile,
+                            PrefixMode::Engine,
+                            PrefixMode::Custom,
+                        ] {
+                            let enabled = mode != PrefixMode::Engine || engine_prefix.is_some();
+                            ui.add_enabled_ui(enabled, |ui| {
+                                ui.selectable_value(&mut prefix_mode, mode, prefix_mode_name(mode));
+                            });
+                        }
+                    });
+                if prefix_mode == PrefixMode::Custom {
+                    ui.add(egui::TextEdit::singleline(&mut prefix).desired_width(200.0));
+                    if ui.button("Browse...").clicked() {
+                        let start_dir =
+                            resolver.resolve_string(if prefix.is_empty() { "~" } else { &prefix });
+                        if let Some(dir) =
+                            tfd::select_folder_dialog("Select Wine Prefix", &start_dir)
+                        {
+                            prefix = resolver.contract(dir);
+                        }
+                    }
+                } else if !prefix.is_empty() {
+                    ui.weak(&prefix)
+                        .on_hover_text(resolver.resolve_string(&prefix));
+                }
+                if !prefix.is_empty() && ui.button("Reset prefix").clicked() {
+                    reset_prefix(&resolver.resolve_string(&prefix));
+                }
+            });
+            ui.end_row();
+
+            ui.label("Game ID:");
+            ui.add(
+                egui::TextEdit::singleline(&mut game_id)
+                    .hint_text("umu-default")
+                    .desired_width(160.0),
+            );
+            ui.end_row();
+
+            ui.label("Store:");
+            option_combo_ui(ui, "umu_store", &mut store, &UMU_STORES);
+            ui.end_row();
+
+            ui.label("Verb:");
+            option_combo_ui(ui, "umu_proton_verb", &mut verb, &UMU_PROTON_VERBS);
+            ui.end_row();
+        });
+    });
+
+    if refresh {
+        refresh_installed_proton_runners(proton_dirs);
+
+        if !installed_proton_runners(proton_dirs)
+            .iter()
+            .any(|r| r.path == proton_runner)
+        {
+            new_proton_runner = Some(String::new());
         }
     }
 
-    let common_dir = format!("{home}/.steam/steam/steamapps/common");
+    if prefix_mode != prefix_mode_before {
+        prefix = match prefix_mode {
+            PrefixMode::Default => String::new(),
+            PrefixMode::Profile => profile_prefix.to_owned(),
+            PrefixMode::Engine => engine_prefix.unwrap_or_default().to_owned(),
+            PrefixMode::Custom => prefix,
+        };
+    }
+
+    let non_empty = |value: &str| Some(value.to_owned()).filter(|v| !v.is_empty());
+    if let Some(runner) = new_proton_runner {
+        set_env_var(env, "PROTONPATH", non_empty(&runner).as_deref());
+    }
+    set_env_var(env, "WINEPREFIX", non_empty(&prefix).as_deref());
+    set_env_var(env, "GAMEID", non_empty(&game_id).as_deref());
+    set_env_var(env, "STORE", non_empty(&store).as_deref());
+    set_env_var(env, "PROTON_VERB", non_empty(&verb).as_deref());
+    // Setting a variable moves it to the end, so only the values are compared.
+    let sorted_env = |env: &str| {
+        let mut pairs = parse_env(env);
+        pairs.sort();
+        pairs
+    };
+    if sorted_env(env) != sorted_env(&env_before) {
+        *store_config = true;
+    } else {
+        *env = env_before;
+    }
+}
 
-    if let Ok(entries) = fs::read_dir(common_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_dir() {
-                continue;
+/// Picks one of the given values, or none to leave the variable unset.
+fn option_combo_ui(ui: &mut egui::Ui, id: &str, value: &mut String, options: &[&str]) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(if value.is_empty() {
+            "<Default>"
+        } else {
+            value.as_str()
+        })
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, String::new(), "<Default>");
+            for option in options {
+                ui.selectable_value(value, option.to_string(), *option);
             }
-            if let Some(name) = entry.file_name().to_str() {
-                if name.contains("Proton") {
-                    options.push(ProtonRunner {
-                        name: name.to_string(),
-                        path: entry.path().to_str().unwrap_or_default().to_owned(),
-                    });
+        });
+}
+
+/// Deletes a Wine prefix after confirmation; it's recreated on the next launch. Only
+/// folders that look like a prefix are deleted.
+fn reset_prefix(prefix: &str) {
+    let path = Path::new(prefix);
+    if !path.exists() {
+        tfd::message_box_ok(
+            "Reset Prefix",
+            "The prefix doesn't exist yet.",
+            tfd::MessageBoxIcon::Info,
+        );
+        return;
+    }
+    let is_prefix = ["drive_c", "pfx", "system.reg"]
+        .iter()
+        .any(|name| path.join(name).exists());
+    if !is_prefix {
+        tfd::message_box_ok(
+            "Reset Prefix",
+            &format!("{} doesn't look like a Wine prefix.", prefix),
+            tfd::MessageBoxIcon::Error,
+        );
+        return;
+    }
+    let confirmed = tfd::message_box_yes_no(
+        "Reset Prefix",
+        &format!(
+            "Delete {}? Everything installed into the prefix is lost.",
+            prefix
+        ),
+        tfd::MessageBoxIcon::Question,
+        tfd::YesNo::No,
+    ) == tfd::YesNo::Yes;
+    if confirmed && let Err(err) = fs::remove_dir_all(path) {
+        tfd::message_box_ok(
+            "Reset Prefix",
+            &format!("{}: {}", prefix, err),
+            tfd::MessageBoxIcon::Error,
+        );
+    }
+}
+
+/// Edits the Wine binary and the `WINEPREFIX`/`WINEDLLOVERRIDES` variables of a Wine
+/// wrapper.
+fn wine_ui(
+    ui: &mut egui::Ui,
+    wrapper: &mut Wrapper,
+    resolver: &PathResolver,
+    store_config: &mut bool,
+) {
+    let mut prefix = env_var(&wrapper.env, "WINEPREFIX").unwrap_or_default();
+    let mut dll_overrides = env_var(&wrapper.env, "WINEDLLOVERRIDES").unwrap_or_default();
+    let prefix_before = prefix.clone();
+    let dll_overrides_before = dll_overrides.clone();
+
+    ui.indent("wine", |ui| {
+        egui::Grid::new("wine_options").show(ui, |ui| {
+            ui.label("Wine binary:");
+            ui.horizontal(|ui| {
+                ui.monospace(&wrapper.executable);
+                if ui.button("Browse...").clicked()
+                    && let Some(path) =
+                        tfd::open_file_dialog("Select Wine Binary", "/usr/bin", None)
+                {
+                    wrapper.executable = resolver.contract(path);
+                    *store_config = true;
                 }
-            }
-        }
+            });
+            ui.end_row();
+
+            ui.label("Prefix:");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut prefix)
+                        .hint_text("$HOME/.wine")
+                        .desired_width(240.0),
+                );
+                if ui.button("Browse...").clicked() {
+                    let start_dir =
+                        resolver.resolve_string(if prefix.is_empty() { "~" } else { &prefix });
+                    if let Some(dir) = tfd::select_folder_dialog("Select Wine Prefix", &start_dir) {
+                        prefix = resolver.contract(dir);
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label("DLL overrides:");
+            ui.add(
+                egui::TextEdit::singleline(&mut dll_overrides)
+                    .hint_text("d3d9=n,b;dinput8=n")
+                    .desired_width(240.0),
+            );
+            ui.end_row();
+        });
+
+        ui.weak("Files are passed to the engine as Z:\\ paths.");
+    });
+
+    if prefix != prefix_before {
+        set_env_var(
+            &mut wrapper.env,
+            "WINEPREFIX",
+            Some(prefix.as_str()).filter(|p| !p.is_empty()),
+        );
+        *store_config = true;
+    }
+    if dll_overrides != dll_overrides_before {
+        set_env_var(
+            &mut wrapper.env,
+            "WINEDLLOVERRIDES",
+            Some(dll_overrides.as_str()).filter(|d| !d.is_empty()),
+        );
+        *store_config = true;
     }
+}
 
-    options.into_iter().collect()
+/// Edits the arguments of a gamescope wrapper through its common options. Arguments
+/// without an option here are kept after them.
+fn gamescope_ui(ui: &mut egui::Ui, args: &mut String, store_config: &mut bool) {
+    let (mut options, rest) = GamescopeOptions::from_args(&split_args(args));
+    let options_before = options.clone();
+
+    let size_ui = |ui: &mut egui::Ui, width: &mut u32, height: &mut u32| {
+        ui.add(egui::DragValue::new(width));
+        ui.label("×");
+        ui.add(egui::DragValue::new(height));
+    };
+
+    ui.indent("gamescope", |ui| {
+        egui::Grid::new("gamescope_options").show(ui, |ui| {
+            ui.label("Output resolution:");
+            ui.horizontal(|ui| {
+                size_ui(ui, &mut options.output_width, &mut options.output_height);
+            });
+            ui.end_row();
+
+            ui.label("Internal resolution:");
+            ui.horizontal(|ui| {
+                size_ui(
+                    ui,
+                    &mut options.internal_width,
+                    &mut options.internal_height,
+                );
+            });
+            ui.end_row();
+
+            ui.label("Refresh rate:");
+            ui.add(egui::DragValue::new(&mut options.refresh_rate).suffix(" Hz"));
+            ui.end_row();
+
+            ui.label("Upscaling:");
+            egui::ComboBox::from_id_salt("gamescope_upscaler")
+                .selected_text(upscaler_name(options.upscaler))
+                .show_ui(ui, |ui| {
+                    for upscaler in [
+                        GamescopeUpscaler::None,
+                        GamescopeUpscaler::Fsr,
+                        GamescopeUpscaler::Nis,
+                    ] {
+                        ui.selectable_value(
+                            &mut options.upscaler,
+                            upscaler,
+                            upscaler_name(upscaler),
+                        );
+                    }
+                });
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut options.fullscreen, "Fullscreen");
+            ui.checkbox(&mut options.hdr, "HDR");
+        });
+
+        ui.weak("Zero values use gamescope's defaults.");
+    });
+
+    if options != options_before {
+        let mut new_args = options.to_args();
+        new_args.extend(rest);
+        *args = join_args(&new_args);
+        *store_config = true;
+    }
+}
+
+fn upscaler_name(upscaler: GamescopeUpscaler) -> &'static str {
+    match upscaler {
+        GamescopeUpscaler::None => "None",
+        GamescopeUpscaler::Fsr => "AMD FSR",
+        GamescopeUpscaler::Nis => "NVIDIA NIS",
+    }
 }
@@ -0,0 +1,101 @@
+use crate::config::Config;
+use std::time::{Duration, Instant};
+
+const MAX_UNDO_STEPS: usize = 100;
+/// Edits made in quick succession (e.g. typing) are merged into one undo step.
+const MERGE_INTERVAL: Duration = Duration::from_millis(750);
+
+/// Undo/redo stacks of config snapshots.
+pub struct ConfigHistory {
+    undo: Vec<Config>,
+    redo: Vec<Config>,
+    last: Config,
+    last_edit: Option<Instant>,
+}
+
+impl ConfigHistory {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last: cfg.clone(),
+            last_edit: None,
+        }
+    }
+
+    /// Records the config state after a user edit. Navigation (switching tabs or pages)
+    /// is not an undo step by itself.
+    pub fn record(&mut self, cfg: &Config) {
+        let mut navigation_only = cfg.clone();
+        navigation_only.active_tab = self.last.active_tab;
+        navigation_only.special_page = self.last.special_page;
+        navigation_only.window_width = self.last.window_width;
+        navigation_only.window_height = self.last.window_height;
+        if navigation_only == self.last {
+            self.last = cfg.clone();
+            return;
+        }
+        let merge = self
+            .last_edit
+            .is_some_and(|time| time.elapsed() < MERGE_INTERVAL);
+        if !merge {
+            self.undo
+                .push(std::mem::replace(&mut self.last, cfg.clone()));
+            if self.undo.len() > MAX_UNDO_STEPS {
+                self.undo.remove(0);
+            }
+        } else {
+            self.last = cfg.clone();
+        }
+        self.redo.clear();
+        self.last_edit = Some(Instant::now());
+    }
+
+    /// Takes in changes that are not undo steps, e.g. play statistics or refreshed file
+    /// hashes, so that undoing the next edit doesn't revert them.
+    pub fn refresh(&mut self, cfg: &Config) {
+        if *cfg != self.last {
+            self.last = cfg.clone();
+        }
+    }
+
+    /// Restores the state before the last edit. Returns true if config changed.
+    pub fn undo(&mut self, cfg: &mut Config) -> bool {
+        match self.undo.pop() {
+            Some(snapshot) => {
+                self.redo.push(cfg.clone());
+                self.restore(cfg, snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the last undone edit. Returns true if config changed.
+    pub fn redo(&mut self, cfg: &mut Config) -> bool {
+        match self.redo.pop() {
+            Some(snapshot) => {
+                self.undo.push(cfg.clone());
+                self.restore(cfg, snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces config with a snapshot, keeping the state that is not edited by the user:
+    /// window size and play statistics.
+    fn restore(&mut self, cfg: &mut Config, mut snapshot: Config) {
+        snapshot.window_width = cfg.window_width;
+        snapshot.window_height = cfg.window_height;
+        for profile in snapshot.tabs.iter_mut().chain(snapshot.library.iter_mut()) {
+            if let Some(current) = cfg.find_profile_mut(profile.id) {
+                profile.last_played = current.last_played;
+                profile.playtime_secs = current.playtime_secs;
+            }
+        }
+        *cfg = snapshot;
+        self.last = cfg.clone();
+        self.last_edit = None;
+    }
+}
@@ -0,0 +1,135 @@
+use crate::app::tab_bar_ui::long_title;
+use crate::config::Config;
+use crate::importers::{ImportPreview, ImportSource, apply_import, read_import};
+use crate::paths::PathResolver;
+use eframe::egui;
+use std::path::Path;
+use tinyfiledialogs as tfd;
+
+/// Renders the "Import from" menu listing the supported launchers. Reading a file opens
+/// its preview.
+pub(super) fn import_menu_ui(
+    ui: &mut egui::Ui,
+    cfg: &Config,
+    import_preview: &mut Option<ImportPreview>,
+) {
+    ui.menu_button("Import from", |ui| {
+        for source in ImportSource::ALL {
+            if !ui.button(format!("{}...", source.name())).clicked() {
+                continue;
+            }
+            let title = format!("Import from {}", source.name());
+            let Some(path) = tfd::open_file_dialog(&title, ".", Some(source.file_filter())) else {
+                continue;
+            };
+            match read_import(source, Path::new(&path), &PathResolver::new(cfg)) {
+                Ok(preview) => *import_preview = Some(preview),
+                Err(err) => tfd::message_box_ok(&title, &err, tfd::MessageBoxIcon::Error),
+            }
+        }
+    });
+}
+
+/// Shows the profiles read from another launcher with the fields that can't be imported,
+/// and imports the selected ones as new tabs.
+pub(super) fn import_preview_ui(
+    ui: &mut egui::Ui,
+    cfg: &mut Config,
+    import_preview: &mut Option<ImportPreview>,
+    store_config: &mut bool,
+) {
+    let Some(preview) = import_preview.as_mut() else {
+        return;
+    };
+    let resolver = PathResolver::new(cfg);
+    let mut open = true;
+    let mut apply = false;
+    let title = format!("Import from {}", preview.source.name());
+    egui::Window::new(title.as_str())
+        .open(&mut open)
+        .collapsible(false)
+        .default_size([560.0, 420.0])
+        .show(ui.ctx(), |ui| {
+            for warning in &preview.warnings {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+            }
+            if preview.profiles.is_empty() {
+                ui.label("<No profiles found>");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(ui.available_height() - 2.0 * ui.spacing().interact_size.y)
+                .show(ui, |ui| {
+                    for (index, imported) in preview.profiles.iter_mut().enumerate() {
+                        let title = long_title(&imported.profile, true);
+                        ui.checkbox(&mut imported.selected, egui::RichText::new(title).strong());
+                        ui.indent(index, |ui| {
+                            let profile = &imported.profile;
+                            path_row_ui(ui, "Engine", profile.engine_path.as_deref(), &resolver);
+                            path_row_ui(ui, "IWAD", profile.iwad_path.as_deref(), &resolver);
+                            for input in &profile.input_paths {
+                                path_row_ui(ui, "File", Some(input), &resolver);
+                            }
+                            if !imported.archive_files.is_empty() {
+                                ui.label(format!(
+                                    "Only from archives: {}",
+                                    imported.archive_files.join(", ")
+                                ));
+                            }
+                            if !profile.extra_args.is_empty() {
+                                ui.label(format!("Arguments: {}", profile.extra_args));
+                            }
+                            for field in &imported.unsupported {
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    format!("Not imported: {}", field),
+                                );
+                            }
+                        });
+                        ui.separator();
+                    }
+                });
+            ui.horizontal(|ui| {
+                let selected = preview.profiles.iter().filter(|p| p.selected).count();
+                if ui
+                    .add_enabled(
+                        selected > 0,
+                        egui::Button::new(format!("Import {} profile(s)", selected)),
+                    )
+                    .clicked()
+                {
+                    apply = true;
+                }
+                if ui.button("Select all").clicked() {
+                    preview.profiles.iter_mut().for_each(|p| p.selected = true);
+                }
+                if ui.button("Select none").clicked() {
+                    preview.profiles.iter_mut().for_each(|p| p.selected = false);
+                }
+            });
+        });
+
+    if apply && let Some(preview) = import_preview.take() {
+        let errors = apply_import(cfg, preview);
+        if !errors.is_empty() {
+            tfd::message_box_ok(&title, &errors.join("\n"), tfd::MessageBoxIcon::Error);
+        }
+        *store_config = true;
+    } else if !open {
+        *import_preview = None;
+    }
+}
+
+/// Shows a path of an imported profile, red if the file doesn't exist.
+fn path_row_ui(ui: &mut egui::Ui, label: &str, path: Option<&str>, resolver: &PathResolver) {
+    let Some(path) = path else {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("{}: <Empty>", label));
+        return;
+    };
+    let text = egui::RichText::new(format!("{}: {}", label, path)).monospace();
+    let text = if resolver.resolve(path).exists() {
+        text
+    } else {
+        text.color(ui.visuals().error_fg_color)
+    };
+    ui.add(egui::Label::new(text).truncate());
+}
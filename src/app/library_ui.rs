@@ -0,0 +1,280 @@
+use crate::app::import_ui::{import_menu_ui, import_preview_ui};
+use crate::app::tab_bar_ui::long_title;
+use crate::app::unix_now;
+use crate::bundle::{export_profiles, import_profiles, is_zip_bundle};
+use crate::config::{Config, LibrarySort, TabConfig};
+use crate::importers::ImportPreview;
+use crate::paths::PathResolver;
+use eframe::egui;
+use std::cmp::Reverse;
+use std::path::Path;
+use tinyfiledialogs as tfd;
+use uuid::Uuid;
+
+const SEARCH_WIDTH: f32 = 240.0;
+
+/// Renders the profile library: every profile, open or not, with search and sorting.
+pub fn library_ui(
+    ui: &mut egui::Ui,
+    cfg: &mut Config,
+    search: &mut String,
+    import_preview: &mut Option<ImportPreview>,
+    store_config: &mut bool,
+) {
+    enum LibraryAction {
+        None,
+        Open(Uuid),
+        Delete(Uuid),
+    }
+
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.add(
+                egui::TextEdit::singleline(search)
+                    .hint_text("Name, tag, WAD, IWAD or engine")
+                    .desired_width(SEARCH_WIDTH),
+            );
+
+            ui.label("Sort by:");
+            let mut current = cfg.library_sort;
+            egui::ComboBox::from_id_salt("library_sort")
+                .selected_text(sort_label(current))
+                .show_ui(ui, |ui| {
+                    for sort in [
+                        LibrarySort::LastPlayed,
+                        LibrarySort::Name,
+                        LibrarySort::Playtime,
+                    ] {
+                        ui.selectable_value(&mut current, sort, sort_label(sort));
+                    }
+                });
+            if current != cfg.library_sort {
+                cfg.library_sort = current;
+                *store_config = true;
+            }
+
+            if ui
+                .button("Import...")
+                .on_hover_text("Import profiles from a bundle")
+                .clicked()
+                && import_profiles_dialog(cfg)
+            {
+                *store_config = true;
+            }
+            import_menu_ui(ui, cfg, import_preview);
+            if ui
+                .button("Export...")
+                .on_hover_text("Export the profiles shown below to a bundle")
+                .clicked()
+            {
+                let ids: Vec<Uuid> = cfg
+                    .tabs
+                    .iter()
+                    .chain(cfg.library.iter())
+                    .filter(|t| t.matches_search(search))
+                    .map(|t| t.id)
+                    .collect();
+                export_profiles_dialog(cfg, &ids);
+            }
+        });
+
+        let mut entries: Vec<(&TabConfig, String, bool)> = cfg
+            .tabs
+            .iter()
+            .map(|t| (t, true))
+            .chain(cfg.library.iter().map(|t| (t, false)))
+            .filter(|(t, _)| t.matches_search(search))
+            .map(|(t, is_open)| (t, long_title(t, true), is_open))
+            .collect();
+        match cfg.library_sort {
+            LibrarySort::LastPlayed => entries.sort_by_key(|(t, _, _)| Reverse(t.last_played)),
+            LibrarySort::Name => entries.sort_by_key(|(_, title, _)| title.to_lowercase()),
+            LibrarySort::Playtime => entries.sort_by_key(|(t, _, _)| Reverse(t.playtime_secs)),
+        }
+
+        let mut action = LibraryAction::None;
+        let now = unix_now();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if entries.is_empty() {
+                ui.label("<No matching profiles>");
+            }
+            egui::Grid::new("library_grid")
+                .striped(true)
+                .num_columns(6)
+                .show(ui, |ui| {
+                    for (tab, title, is_open) in &entries {
+                        if ui
+                            .button(if *is_open { "Show" } else { "Open" })
+                            .on_hover_text(if *is_open {
+                                "Switch to the tab of this profile"
+                            } else {
+                                "Open this profile in a new tab"
+                            })
+                            .clicked()
+                        {
+                            action = LibraryAction::Open(tab.id);
+                        }
+                        ui.label(egui::RichText::new(title).strong())
+                            .context_menu(|ui| {
+                                if ui.button("Export...").clicked() {
+                                    export_profiles_dialog(cfg, &[tab.id]);
+                                }
+                            });
+                        ui.label(tab.tags.join(", "));
+                        ui.label(file_name_or_empty(tab.iwad_path.as_deref()))
+                            .on_hover_text(file_name_or_empty(tab.engine_path.as_deref()));
+                        ui.label(match tab.last_played {
+                            Some(time) => format_time_ago(now.saturating_sub(time)),
+                            None => "Never".to_owned(),
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(format_duration(tab.playtime_secs));
+                            if !*is_open
+                                && ui
+                                    .button("×")
+                                    .on_hover_text("Delete profile permanently")
+                                    .clicked()
+                            {
+                                action = LibraryAction::Delete(tab.id);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+
+        import_preview_ui(ui, cfg, import_preview, store_config);
+
+        match action {
+            LibraryAction::Open(id) => {
+                cfg.open_profile(id);
+                *store_config = true;
+            }
+            LibraryAction::Delete(id) => {
+                cfg.delete_library_profile(id);
+                *store_config = true;
+            }
+            LibraryAction::None => {}
+        }
+    });
+}
+
+const BUNDLE_FILTER: (&[&str], &str) = (
+    &["*.toml", "*.json", "*.zip"],
+    "Profile bundles (*.toml, *.json, *.zip)",
+);
+
+/// Asks for a bundle file and exports the given profiles to it. A `.zip` bundle includes
+/// the input files.
+pub(super) fn export_profiles_dialog(cfg: &Config, ids: &[Uuid]) {
+    let profiles: Vec<&TabConfig> = ids
+        .iter()
+        .filter_map(|id| {
+            cfg.tabs
+                .iter()
+                .chain(cfg.library.iter())
+                .find(|t| t.id == *id)
+        })
+        .collect();
+    let default_name = match profiles.as_slice() {
+        [profile] => format!(
+            "{}.toml",
+            long_title(profile, false).replace(['/', '\\'], "_")
+        ),
+        _ => "profiles.toml".to_owned(),
+    };
+    let Some(path) = tfd::save_file_dialog_with_filter(
+        "Export Profiles",
+        &default_name,
+        BUNDLE_FILTER.0,
+        BUNDLE_FILTER.1,
+    ) else {
+        return;
+    };
+    match export_profiles(&profiles, &PathResolver::new(cfg), Path::new(&path)) {
+        Ok(()) => tfd::message_box_ok(
+            "Export profiles",
+            &format!("Exported {} profile(s) to {}.", profiles.len(), path),
+            tfd::MessageBoxIcon::Info,
+        ),
+        Err(err) => tfd::message_box_ok("Export profiles", &err, tfd::MessageBoxIcon::Error),
+    }
+}
+
+/// Asks for a bundle file and imports its profiles as new tabs. Returns true if profiles
+/// were imported.
+fn import_profiles_dialog(cfg: &mut Config) -> bool {
+    let Some(path) = tfd::open_file_dialog("Import Profiles", ".", Some(BUNDLE_FILTER)) else {
+        return false;
+    };
+    let path = Path::new(&path);
+    let extract_dir = if is_zip_bundle(path) {
+        let start_dir = cfg.library_dirs.first().map(String::as_str).unwrap_or(".");
+        tfd::select_folder_dialog("Extract Included Files To", start_dir)
+    } else {
+        None
+    };
+    match import_profiles(cfg, path, extract_dir.as_deref().map(Path::new)) {
+        Ok(report) => {
+            let mut message = format!("Imported {} profile(s).", report.profiles);
+            if !report.mismatched.is_empty() {
+                message.push_str(&format!(
+                    "\n\nFound with different contents:\n{}",
+                    report.mismatched.join("\n")
+                ));
+            }
+            if !report.missing.is_empty() {
+                message.push_str(&format!(
+                    "\n\nMissing (relink them once available):\n{}",
+                    report.missing.join("\n")
+                ));
+            }
+            tfd::message_box_ok("Import profiles", &message, tfd::MessageBoxIcon::Info);
+            report.profiles > 0
+        }
+        Err(err) => {
+            tfd::message_box_ok("Import profiles", &err, tfd::MessageBoxIcon::Error);
+            false
+        }
+    }
+}
+
+fn sort_label(sort: LibrarySort) -> &'static str {
+    match sort {
+        LibrarySort::LastPlayed => "Last played",
+        LibrarySort::Name => "Name",
+        LibrarySort::Playtime => "Playtime",
+    }
+}
+
+fn file_name_or_empty(path: Option<&str>) -> String {
+    path.map(|p| {
+        Path::new(p)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or(p)
+            .to_owned()
+    })
+    .unwrap_or_default()
+}
+
+fn format_duration(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub(super) fn format_time_ago(secs: u64) -> String {
+    match secs {
+        0..60 => "Just now".to_owned(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
@@ -1,13 +1,32 @@
 #[path = "game_profile_ui/_mod.rs"]
 mod game_profile_ui;
+mod history;
+mod import_ui;
+mod library_ui;
 mod settings_ui;
+mod speedrun_ui;
 mod tab_bar_ui;
 
 use arboard::Clipboard;
 use eframe::egui;
 use eframe::egui::ColorImage;
+use std::process::{Child, Command};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tinyfiledialogs as tfd;
+use uuid::Uuid;
 
-use crate::config::Config;
+use crate::app::history::ConfigHistory;
+use crate::archive::wait_for_archive_checks;
+use crate::config::{Config, SpecialPage};
+use crate::config_file::ConfigSaver;
+use crate::importers::ImportPreview;
+use crate::keymap::{ShortcutAction, is_function_key};
+use crate::launch::build_launch_cmds;
+use crate::paths::PathResolver;
+use crate::profile_data::{create_profile_data_dirs, is_isolated, profile_data_dir};
+use crate::relink::{FileHashes, apply_file_hashes, start_file_hashing};
+use crate::speedrun::{SpeedrunResult, SpeedrunRun, finish_speedrun};
 use crate::wad::{
     decode_htitle, decode_titlepic, get_titlepic_dimensions, load_playpal_lump, load_titlepic_lump,
 };
@@ -18,9 +37,68 @@ pub struct App {
     pub titlepic_texture: Option<egui::TextureHandle>,
     pub last_iwad_path: Option<String>,
     pub last_wad_path: Option<String>,
+    pub library_search: String,
+    pub import_preview: Option<ImportPreview>,
+    pub running_games: Vec<RunningGame>,
+    /// Content hashes of launched profiles' files, being computed on worker threads.
+    pub file_hash_jobs: Vec<Receiver<FileHashes>>,
+    /// Analysis of the last dsda-doom speedrun, shown until closed.
+    pub speedrun_result: Option<SpeedrunResult>,
+    pub history: ConfigHistory,
+    pub config_saver: ConfigSaver,
+}
+
+/// A launched game process, tracked to accumulate the playtime of its profile.
+pub struct RunningGame {
+    pub profile_id: Uuid,
+    pub child: Child,
+    pub started: Instant,
+    pub speedrun: Option<SpeedrunRun>,
+    /// Servers and local test players started along with a multiplayer game, stopped
+    /// when the game exits.
+    pub helpers: Vec<Child>,
+}
+
+impl RunningGame {
+    fn stop_helpers(&mut self) {
+        stop_processes(&mut self.helpers);
+    }
+}
+
+/// Kills the processes and waits for them to exit, so their ports are freed.
+fn stop_processes(children: &mut Vec<Child>) {
+    for mut child in children.drain(..) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Returns the current time as a Unix timestamp in seconds.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
 }
 
 impl App {
+    pub fn new(config: Config) -> Self {
+        Self {
+            history: ConfigHistory::new(&config),
+            config,
+            clipboard: Clipboard::new().unwrap(),
+            titlepic_texture: None,
+            last_iwad_path: None,
+            last_wad_path: None,
+            library_search: String::new(),
+            import_preview: None,
+            running_games: Vec::new(),
+            file_hash_jobs: Vec::new(),
+            speedrun_result: None,
+            config_saver: ConfigSaver::new(),
+        }
+    }
+
     pub fn load_titlepic(
         &mut self,
         ui: &mut egui::Ui,
@@ -61,13 +139,232 @@ impl App {
         false
     }
 
+    /// Handles keyboard shortcuts from the keymap. Sets `config_edited` on edits that are
+    /// recorded for undo and returns true if config changed otherwise (undo/redo).
+    fn handle_shortcuts(
+        &mut self,
+        ui: &mut egui::Ui,
+        config_edited: &mut bool,
+        cmds_to_launch: &mut Vec<Command>,
+    ) -> bool {
+        let typing = ui.ctx().egui_wants_keyboard_input();
+        let action = self
+            .config
+            .keymap
+            .shortcuts()
+            .into_iter()
+            // Leave the keys to text fields, except plain function keys they don't use.
+            .filter(|(action, shortcut)| {
+                !typing
+                    || (*action != ShortcutAction::RenameProfile
+                        && shortcut.modifiers.is_none()
+                        && is_function_key(shortcut.logical_key))
+            })
+            .find(|(_, shortcut)| ui.input_mut(|i| i.consume_shortcut(shortcut)))
+            .map(|(action, _)| action);
+        let Some(action) = action else {
+            return false;
+        };
+
+        let cfg = &mut self.config;
+        let has_active_tab = cfg.get_active_tab_index().is_some();
+        match action {
+            // Text fields have their own undo, don't interfere with them.
+            ShortcutAction::Undo if !typing => return self.history.undo(cfg),
+            ShortcutAction::Redo if !typing => return self.history.redo(cfg),
+            ShortcutAction::Undo | ShortcutAction::Redo => {}
+            ShortcutAction::NextTab | ShortcutAction::PreviousTab => {
+                let ids = tab_bar_ui::visible_tab_ids(cfg);
+                if !ids.is_empty() {
+                    let next = match cfg
+                        .active_tab
+                        .and_then(|id| ids.iter().position(|t| *t == id))
+                    {
+                        Some(index) if action == ShortcutAction::NextTab => (index + 1) % ids.len(),
+                        Some(index) => (index + ids.len() - 1) % ids.len(),
+                        None => 0,
+                    };
+                    cfg.active_tab = Some(ids[next]);
+                    *config_edited = true;
+                }
+            }
+            ShortcutAction::GoToTab(number) => {
+                if let Some(id) = tab_bar_ui::visible_tab_ids(cfg).get(number - 1) {
+                    cfg.active_tab = Some(*id);
+                    *config_edited = true;
+                }
+            }
+            ShortcutAction::NewTab => {
+                cfg.new_tab(tab_bar_ui::active_tab_group(cfg));
+                *config_edited = true;
+            }
+            ShortcutAction::CloseTab => {
+                if let Some(index) = cfg.get_active_tab_index()
+                    && cfg.tabs.len() > 1
+                {
+                    cfg.close_tab(index);
+                    *config_edited = true;
+                }
+            }
+            ShortcutAction::ReopenClosedTab => {
+                *config_edited |= cfg.reopen_closed_tab();
+            }
+            ShortcutAction::Launch if has_active_tab => {
+                *cmds_to_launch = build_launch_cmds(cfg);
+            }
+            ShortcutAction::AddInputFile if has_active_tab => {
+                *config_edited |= game_profile_ui::add_input_file_dialog(cfg);
+            }
+            ShortcutAction::RenameProfile if has_active_tab => {
+                ui.data_mut(|d| {
+                    d.insert_temp(egui::Id::new(game_profile_ui::RENAME_REQUEST_ID), true)
+                });
+            }
+            ShortcutAction::Launch
+            | ShortcutAction::AddInputFile
+            | ShortcutAction::RenameProfile => {}
+        }
+        false
+    }
+
+    /// Spawns the game and records the launch time of the active profile. The last command
+    /// is the player's game, the others are servers and other players of a multiplayer game.
+    fn launch_game(&mut self, mut cmds: Vec<Command>) {
+        let Some(mut cmd) = cmds.pop() else {
+            return;
+        };
+        let tab_config = self.config.get_active_tab();
+        let resolver = PathResolver::new(&self.config);
+        // Archives may still be re-extracting in the background.
+        let errors = wait_for_archive_checks(&tab_config.archives, &resolver);
+        if !errors.is_empty() {
+            tfd::message_box_ok(
+                "Failed to launch",
+                &errors.join("\n"),
+                tfd::MessageBoxIcon::Error,
+            );
+            return;
+        }
+        if is_isolated(tab_config) {
+            let dir = PathResolver::new(&self.config).resolve(&profile_data_dir(tab_config));
+            if let Err(err) = create_profile_data_dirs(tab_config, &dir) {
+                eprintln!("Failed to create {}: {}", dir.display(), err);
+            }
+        }
+        let mut helpers = Vec::new();
+        for mut helper in cmds {
+            match helper.spawn() {
+                Ok(child) => helpers.push(child),
+                Err(err) => {
+                    // There would be no game to join, don't start the player's one.
+                    stop_processes(&mut helpers);
+                    tfd::message_box_ok(
+                        "Failed to launch",
+                        &format!(
+                            "Failed to start {}: {}",
+                            helper.get_program().to_string_lossy(),
+                            err
+                        ),
+                        tfd::MessageBoxIcon::Error,
+                    );
+                    return;
+                }
+            }
+        }
+        let speedrun = SpeedrunRun::from_cmd(&cmd);
+        match cmd.spawn() {
+            Ok(child) => {
+                let resolver = PathResolver::new(&self.config);
+                let tab_config = self.config.get_active_tab_mut();
+                tab_config.last_played = Some(unix_now());
+                self.file_hash_jobs
+                    .extend(start_file_hashing(tab_config, &resolver));
+                self.running_games.push(RunningGame {
+                    profile_id: tab_config.id,
+                    child,
+                    started: Instant::now(),
+                    speedrun,
+                    helpers,
+                });
+            }
+            Err(err) => {
+                stop_processes(&mut helpers);
+                tfd::message_box_ok(
+                    "Failed to launch",
+                    &format!(
+                        "Failed to start {}: {}",
+                        cmd.get_program().to_string_lossy(),
+                        err
+                    ),
+                    tfd::MessageBoxIcon::Error,
+                );
+            }
+        }
+    }
+
+    /// Polls running games and adds playtime of exited ones to their profiles. Speedruns
+    /// get their analysis read. Returns true if config changed.
+    fn poll_running_games(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut config_changed = false;
+        let config = &mut self.config;
+        let speedrun_result = &mut self.speedrun_result;
+        self.running_games.retain_mut(|game| {
+            if matches!(game.child.try_wait(), Ok(None)) {
+                return true;
+            }
+            game.stop_helpers();
+            if let Some(profile) = config.find_profile_mut(game.profile_id) {
+                profile.playtime_secs += game.started.elapsed().as_secs();
+                config_changed = true;
+                if let Some(run) = &game.speedrun
+                    && let Some(result) = finish_speedrun(run, profile)
+                {
+                    *speedrun_result = Some(result);
+                }
+            }
+            false
+        });
+        if !self.running_games.is_empty() {
+            ui.request_repaint_after(Duration::from_secs(1));
+        }
+        config_changed
+    }
+
+    /// Records the file hashes of finished hashing jobs. Returns true if config changed.
+    fn poll_file_hash_jobs(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut config_changed = false;
+        let config = &mut self.config;
+        self.file_hash_jobs.retain(|job| match job.try_recv() {
+            Ok(hashes) => {
+                if let Some(profile) = config.find_profile_mut(hashes.profile_id) {
+                    apply_file_hashes(profile, hashes);
+                    config_changed = true;
+                }
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        if !self.file_hash_jobs.is_empty() {
+            ui.request_repaint_after(Duration::from_millis(200));
+        }
+        config_changed
+    }
+
     /// Checks if TITLEPIC needs to be reloaded and reloads if needed.
     fn reload_titlepic_if_needed(&mut self, ui: &mut egui::Ui) {
         let cfg = &self.config;
         if cfg.active_tab != None {
             let tab_config = cfg.get_active_tab();
-            let iwad_path = tab_config.iwad_path.clone();
-            let wad_path = tab_config.input_paths.get(0).cloned();
+            let resolver = PathResolver::new(cfg);
+            let iwad_path = tab_config
+                .iwad_path
+                .as_ref()
+                .map(|p| resolver.resolve_string(p));
+            let wad_path = tab_config
+                .input_paths
+                .first()
+                .map(|p| resolver.resolve_string(p));
             let mut need_titlepic = false;
             if wad_path.is_some() || iwad_path.is_some() {
                 need_titlepic = self.last_iwad_path.as_deref() != iwad_path.as_deref()
@@ -86,14 +383,42 @@ impl App {
 impl eframe::App for App {
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
         let mut should_store_config = self.handle_window_resize(ui);
+        should_store_config |= self.poll_running_games(ui);
+        should_store_config |= self.poll_file_hash_jobs(ui);
+        // Set by UI on user edits, which are recorded for undo.
+        let mut config_edited = false;
+        let mut cmds_to_launch: Vec<Command> = Vec::new();
+        should_store_config |= self.handle_shortcuts(ui, &mut config_edited, &mut cmds_to_launch);
         self.reload_titlepic_if_needed(ui);
         let mut input_path_indexes_to_remove = Vec::new();
         let mut iwad_to_load: Option<String> = None;
-        tab_bar_ui::tab_bar_ui(&mut self.config, ui, &mut should_store_config);
+        tab_bar_ui::tab_bar_ui(&mut self.config, ui, &mut config_edited);
         let cfg = &mut self.config;
 
-        if cfg.active_tab == None {
-            settings_ui::settings_ui(ui, cfg, &mut should_store_config);
+        if cfg.active_tab.is_none() {
+            // Dropped files are added to the shown profile, there's none on these pages.
+            if ui.input(|i| !i.raw.dropped_files.is_empty()) {
+                tfd::message_box_ok(
+                    "Files not added",
+                    "Dropped files are added to the profile that is shown. Open a profile \
+                     tab and drop them there.",
+                    tfd::MessageBoxIcon::Info,
+                );
+            }
+            match cfg.special_page {
+                SpecialPage::Settings => {
+                    settings_ui::settings_ui(ui, cfg, &mut config_edited);
+                }
+                SpecialPage::Library => {
+                    library_ui::library_ui(
+                        ui,
+                        cfg,
+                        &mut self.library_search,
+                        &mut self.import_preview,
+                        &mut config_edited,
+                    );
+                }
+            }
         } else {
             game_profile_ui::game_profile_ui(
                 &self.titlepic_texture,
@@ -102,19 +427,46 @@ impl eframe::App for App {
                 cfg,
                 &mut input_path_indexes_to_remove,
                 &mut iwad_to_load,
-                &mut should_store_config,
+                &mut cmds_to_launch,
+                &mut config_edited,
             );
 
             input_path_indexes_to_remove.sort();
 
             for index in input_path_indexes_to_remove.iter().rev() {
                 cfg.get_active_tab_mut().input_paths.remove(*index);
-                should_store_config = true;
+                config_edited = true;
+            }
+            if !input_path_indexes_to_remove.is_empty() {
+                cfg.get_active_tab_mut().prune_archives();
             }
         }
 
+        speedrun_ui::speedrun_result_ui(ui, &mut self.speedrun_result);
+
+        if config_edited {
+            self.history.record(&self.config);
+            should_store_config = true;
+        }
+
+        if !cmds_to_launch.is_empty() {
+            self.launch_game(cmds_to_launch);
+            should_store_config = true;
+        }
+
         if should_store_config {
-            confy::store("wadlauncher", None, &*cfg).unwrap();
+            self.history.refresh(&self.config);
+            self.config_saver.mark_dirty();
+        }
+        if let Some(wait) = self.config_saver.save_if_due(&self.config) {
+            ui.request_repaint_after(wait);
+        }
+    }
+
+    fn on_exit(&mut self) {
+        for game in &mut self.running_games {
+            game.stop_helpers();
         }
+        self.config_saver.flush(&self.config);
     }
 }
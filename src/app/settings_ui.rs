@@ -1,7 +1,16 @@
-use crate::config::{Config, TitleMode};
+use crate::config::{Config, RelativePathBase, TitleMode};
+use crate::dirs::{config_file, is_portable};
+use crate::keymap::{Keymap, is_valid_binding};
+use crate::paths::{BUILTIN_VARIABLES, PathResolver};
+#[cfg(target_os = "linux")]
+use crate::proton::refresh_installed_proton_runners;
 use eframe::egui;
+use tinyfiledialogs as tfd;
 
-/// Renders the Settings UI. Provides Title Mode dropdown and Show Command Line checkbox.
+const BINDING_WIDTH: f32 = 140.0;
+
+/// Renders the Settings UI. Provides Title Mode dropdown, Show Command Line checkbox
+/// and the keyboard shortcut reference.
 pub fn settings_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
     egui::CentralPanel::default().show_inside(ui, |ui| {
         ui.horizontal(|ui| {
@@ -45,5 +54,218 @@ pub fn settings_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool)
                 *store_config = true;
             }
         });
+
+        ui.separator();
+        library_dirs_ui(ui, cfg, store_config);
+        ui.separator();
+        #[cfg(target_os = "linux")]
+        {
+            proton_dirs_ui(ui, cfg, store_config);
+            ui.separator();
+        }
+        path_variables_ui(ui, cfg, store_config);
+        ui.separator();
+        shortcuts_ui(ui, cfg, store_config);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Config file:");
+            ui.label(egui::RichText::new(config_file().to_string_lossy()).monospace());
+            if is_portable() {
+                ui.weak("(portable)");
+            }
+        });
+    });
+}
+
+/// Lists folders searched when relinking missing files.
+fn library_dirs_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Library folders:");
+        if ui.button("Add").clicked() {
+            let start_dir = cfg.library_dirs.last().map(String::as_str).unwrap_or(".");
+            if let Some(dir) = tfd::select_folder_dialog("Add Library Folder", start_dir) {
+                cfg.library_dirs.push(dir);
+                *store_config = true;
+            }
+        }
+    });
+    ui.group(|ui| {
+        if cfg.library_dirs.is_empty() {
+            ui.label("<Empty>");
+        }
+        let mut index_to_remove = None;
+        for (index, dir) in cfg.library_dirs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("×").clicked() {
+                    index_to_remove = Some(index);
+                }
+                ui.label(egui::RichText::new(dir).monospace());
+            });
+        }
+        if let Some(index) = index_to_remove {
+            cfg.library_dirs.remove(index);
+            *store_config = true;
+        }
+    });
+}
+
+/// Lists folders searched for Proton runners, either runners or folders containing them.
+#[cfg(target_os = "linux")]
+fn proton_dirs_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Proton folders:");
+        if ui.button("Add").clicked() {
+            let start_dir = cfg.proton_dirs.last().map(String::as_str).unwrap_or(".");
+            if let Some(dir) = tfd::select_folder_dialog("Add Proton Folder", start_dir) {
+                cfg.proton_dirs.push(dir);
+                changed = true;
+            }
+        }
+    });
+    ui.group(|ui| {
+        if cfg.proton_dirs.is_empty() {
+            ui.label("<Empty>");
+        }
+        let mut index_to_remove = None;
+        for (index, dir) in cfg.proton_dirs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("×").clicked() {
+                    index_to_remove = Some(index);
+                }
+                ui.label(egui::RichText::new(dir).monospace());
+            });
+        }
+        if let Some(index) = index_to_remove {
+            cfg.proton_dirs.remove(index);
+            changed = true;
+        }
+    });
+    if changed {
+        refresh_installed_proton_runners(&cfg.proton_dirs);
+        *store_config = true;
+    }
+}
+
+/// Edits user-defined path roots and the base directory of relative paths.
+fn path_variables_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Relative paths are resolved against:");
+        let mut current = cfg.relative_path_base;
+        egui::ComboBox::from_id_salt("relative_path_base")
+            .selected_text(relative_path_base_label(current))
+            .show_ui(ui, |ui| {
+                for base in [RelativePathBase::ConfigDir, RelativePathBase::ExecutableDir] {
+                    ui.selectable_value(&mut current, base, relative_path_base_label(base));
+                }
+            });
+        if current != cfg.relative_path_base {
+            cfg.relative_path_base = current;
+            *store_config = true;
+        }
+    });
+
+    let new_name_id = ui.id().with("new_path_variable");
+    let mut new_name: String = ui.data_mut(|d| d.get_temp(new_name_id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.label("Path variables:");
+        let valid = !new_name.is_empty()
+            && new_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !BUILTIN_VARIABLES.contains(&new_name.as_str())
+            && !cfg.path_variables.contains_key(&new_name);
+        ui.add(
+            egui::TextEdit::singleline(&mut new_name)
+                .hint_text("name")
+                .desired_width(BINDING_WIDTH),
+        );
+        if ui
+            .add_enabled(valid, egui::Button::new("Add"))
+            .on_disabled_hover_text(
+                "Use a new name of letters, digits and _, other than cache, config, data and exe",
+            )
+            .clicked()
+        {
+            let start_dir = cfg.library_dirs.first().map(String::as_str).unwrap_or(".");
+            if let Some(value) = tfd::select_folder_dialog("Select Variable Folder", start_dir) {
+                cfg.path_variables
+                    .insert(std::mem::take(&mut new_name), value);
+                *store_config = true;
+            }
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(new_name_id, new_name));
+
+    let resolver = PathResolver::new(cfg);
+    ui.group(|ui| {
+        if cfg.path_variables.is_empty() {
+            ui.label("<Empty>");
+        }
+        let mut name_to_remove = None;
+        egui::Grid::new("path_variables_grid")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (name, value) in cfg.path_variables.iter_mut() {
+                    ui.horizontal(|ui| {
+                        if ui.button("×").clicked() {
+                            name_to_remove = Some(name.clone());
+                        }
+                        ui.label(egui::RichText::new(format!("${{{}}}", name)).monospace());
+                    });
+                    if ui
+                        .add(egui::TextEdit::singleline(value).code_editor())
+                        .changed()
+                    {
+                        *store_config = true;
+                    }
+                    ui.weak(resolver.resolve_string(&format!("${{{}}}", name)));
+                    ui.end_row();
+                }
+            });
+        if let Some(name) = name_to_remove {
+            cfg.path_variables.remove(&name);
+            *store_config = true;
+        }
+    });
+    ui.label(
+        "Use variables in profile paths as ${name}. Built-in: ${cache}, ${config}, ${data}, ${exe}; \
+         environment variables like $HOME or $DOOMWADDIR work too. New files are stored \
+         relative to the longest matching variable.",
+    );
+}
+
+fn relative_path_base_label(base: RelativePathBase) -> &'static str {
+    match base {
+        RelativePathBase::ConfigDir => "Config folder",
+        RelativePathBase::ExecutableDir => "Launcher folder",
+    }
+}
+
+/// Lists keyboard shortcuts and lets them be remapped. Invalid bindings are shown in red
+/// and ignored.
+fn shortcuts_ui(ui: &mut egui::Ui, cfg: &mut Config, store_config: &mut bool) {
+    egui::CollapsingHeader::new("Keyboard shortcuts").show(ui, |ui| {
+        egui::Grid::new("keymap_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (description, modifiers_only, binding) in cfg.keymap.entries_mut() {
+                    ui.label(description);
+                    let valid = is_valid_binding(binding, modifiers_only);
+                    let mut edit = egui::TextEdit::singleline(binding).desired_width(BINDING_WIDTH);
+                    if !valid {
+                        edit = edit.text_color(ui.visuals().error_fg_color);
+                    }
+                    if ui.add(edit).changed() {
+                        *store_config = true;
+                    }
+                    ui.end_row();
+                }
+            });
+        ui.label("Bindings look like Ctrl+Shift+T; leave empty to disable.");
+        if ui.button("Reset to defaults").clicked() {
+            cfg.keymap = Keymap::default();
+            *store_config = true;
+        }
     });
 }
@@ -0,0 +1,85 @@
+use crate::speedrun::SpeedrunResult;
+use eframe::egui;
+
+/// Shows the analysis of the last speedrun: level times, the category dsda-doom detected
+/// against the one the profile runs, and the name the demo was saved under.
+pub(super) fn speedrun_result_ui(ui: &mut egui::Ui, speedrun_result: &mut Option<SpeedrunResult>) {
+    let Some(result) = speedrun_result.as_ref() else {
+        return;
+    };
+    let mut open = true;
+    let title = if result.profile.is_empty() {
+        "Run analysis".to_string()
+    } else {
+        format!("Run analysis - {}", result.profile)
+    };
+    egui::Window::new(title.as_str())
+        .open(&mut open)
+        .collapsible(false)
+        .show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Category:");
+                let detected = result.detected_category().unwrap_or("<Unknown>");
+                if result.category_matches() {
+                    ui.strong(detected);
+                } else {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} (running {})", detected, result.category.name()),
+                    );
+                }
+            });
+            match &result.demo {
+                Some(demo) => {
+                    let name = demo
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    ui.label(format!("Demo: {}", name))
+                        .on_hover_text(demo.to_string_lossy());
+                }
+                None => {
+                    ui.weak("No demo recorded");
+                }
+            }
+            if let Some(error) = &result.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            if !result.levels.is_empty() {
+                ui.separator();
+                egui::Grid::new("speedrun_levels")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (map, time) in &result.levels {
+                            ui.label(map);
+                            ui.monospace(time);
+                            ui.end_row();
+                        }
+                        if let Some(total) = &result.total {
+                            ui.strong("Total");
+                            ui.monospace(total);
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            ui.separator();
+            egui::Grid::new("speedrun_analysis")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (key, value) in &result.analysis {
+                        if key == "category" {
+                            continue;
+                        }
+                        ui.label(key.replace('_', " "));
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                });
+        });
+    if !open {
+        *speedrun_result = None;
+    }
+}
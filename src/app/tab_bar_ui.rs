@@ -1,6 +1,10 @@
-use crate::config::{Config, TabConfig};
+use crate::app::library_ui::export_profiles_dialog;
+use crate::config::{Config, SpecialPage, TabConfig, TabGroup};
+use crate::flatpak::flatpak_app_id;
+use crate::launch::{env_var, profile_prefix, set_env_var};
 use eframe::egui;
 use egui_dnd::dnd;
+use egui_dnd::utils::shift_vec;
 use std::path::Path;
 use uuid::Uuid;
 
@@ -28,92 +32,104 @@ fn sanitize_tab_name_part(s: &str) -> String {
     out.to_uppercase().trim().to_string()
 }
 
-fn build_long_titles(cfg: &Config) -> Vec<String> {
-    cfg.tabs
-        .iter()
-        .map(|tab| {
-            if tab.engine_path.is_none() && tab.iwad_path.is_none() && tab.input_paths.is_empty() {
-                "New Tab".to_owned()
+/// Builds the long title of a profile: custom name, or the first input with engine/IWAD in brackets.
+pub(super) fn long_title(tab: &TabConfig, show_iwad: bool) -> String {
+    if let Some(name) = tab.name.as_ref() {
+        return name.clone();
+    }
+    if tab.engine_path.is_none() && tab.iwad_path.is_none() && tab.input_paths.is_empty() {
+        "New Tab".to_owned()
+    } else {
+        // Prefer showing the first input (wad) as the primary title, with engine/iwad in parentheses.
+        let wad_name = tab.input_paths.get(0).map(|wad| {
+            sanitize_tab_name_part(
+                &Path::new(wad)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(wad),
+            )
+        });
+        let engine_name = tab.engine_path.as_ref().map(|engine| {
+            // Flatpak engines are named after the last part of the app id.
+            let name = match flatpak_app_id(engine) {
+                Some(app_id) => app_id.rsplit('.').next().unwrap_or(app_id),
+                None => Path::new(engine)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(engine),
+            };
+            sanitize_tab_name_part(name)
+        });
+        let iwad_name = tab.iwad_path.as_ref().map(|iwad| {
+            sanitize_tab_name_part(
+                &Path::new(iwad)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(iwad),
+            )
+        });
+
+        if let Some(wad) = wad_name {
+            let mut extras: Vec<String> = Vec::new();
+            if let Some(engine) = engine_name {
+                extras.push(engine);
+            }
+            if show_iwad {
+                if let Some(iwad) = iwad_name {
+                    extras.push(iwad);
+                }
+            }
+            if extras.is_empty() {
+                wad
             } else {
-                // Prefer showing the first input (wad) as the primary title, with engine/iwad in parentheses.
-                let wad_name = tab.input_paths.get(0).map(|wad| {
-                    sanitize_tab_name_part(
-                        &Path::new(wad)
-                            .file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or(wad),
-                    )
-                });
-                let engine_name = tab.engine_path.as_ref().map(|engine| {
-                    sanitize_tab_name_part(
-                        &Path::new(engine)
-                            .file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or(engine),
-                    )
-                });
-                let iwad_name = tab.iwad_path.as_ref().map(|iwad| {
-                    sanitize_tab_name_part(
-                        &Path::new(iwad)
-                            .file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or(iwad),
-                    )
-                });
-
-                if let Some(wad) = wad_name {
-                    let mut extras: Vec<String> = Vec::new();
-                    if let Some(engine) = engine_name {
-                        extras.push(engine);
-                    }
-                    if cfg.show_iwad_in_long_titles {
-                        if let Some(iwad) = iwad_name {
-                            extras.push(iwad);
-                        }
-                    }
-                    if extras.is_empty() {
-                        wad
-                    } else {
-                        format!("{} [{}]", wad, extras.join(", "))
-                    }
+                format!("{} [{}]", wad, extras.join(", "))
+            }
+        } else {
+            // Fallback: if there's no wad, prefer iwad as primary and show engine in parentheses.
+            if let Some(iwad) = iwad_name {
+                if let Some(engine) = engine_name {
+                    format!("{} [{}]", iwad, engine)
                 } else {
-                    // Fallback: if there's no wad, prefer iwad as primary and show engine in parentheses.
-                    if let Some(iwad) = iwad_name {
-                        if let Some(engine) = engine_name {
-                            format!("{} [{}]", iwad, engine)
-                        } else {
-                            iwad
-                        }
-                    } else if let Some(engine) = engine_name {
-                        engine
-                    } else {
-                        "New Tab".to_owned()
-                    }
+                    iwad
                 }
+            } else if let Some(engine) = engine_name {
+                engine
+            } else {
+                "New Tab".to_owned()
             }
+        }
+    }
+}
+
+/// Builds the short title of a profile: custom name, or the first input name.
+pub(super) fn short_title(tab: &TabConfig) -> String {
+    if let Some(name) = tab.name.as_ref() {
+        return name.clone();
+    }
+    tab.input_paths
+        .get(0)
+        .map(|wad| {
+            sanitize_tab_name_part(
+                &Path::new(wad)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(wad),
+            )
         })
-        .collect()
+        .unwrap_or_else(|| "New Tab".to_owned())
 }
 
-fn build_short_titles(cfg: &Config) -> Vec<String> {
+fn build_long_titles(cfg: &Config) -> Vec<String> {
     cfg.tabs
         .iter()
-        .map(|tab| {
-            tab.input_paths
-                .get(0)
-                .map(|wad| {
-                    sanitize_tab_name_part(
-                        &Path::new(wad)
-                            .file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or(wad),
-                    )
-                })
-                .unwrap_or_else(|| "New Tab".to_owned())
-        })
+        .map(|tab| long_title(tab, cfg.show_iwad_in_long_titles))
         .collect()
 }
 
+fn build_short_titles(cfg: &Config) -> Vec<String> {
+    cfg.tabs.iter().map(short_title).collect()
+}
+
 fn compute_long_width(ui: &egui::Ui, long_titles: &[String]) -> f32 {
     let font_id = egui::TextStyle::Button.resolve(ui.style());
     let mut long_width = 0.0;
@@ -125,25 +141,145 @@ fn compute_long_width(ui: &egui::Ui, long_titles: &[String]) -> f32 {
     }
     long_width += (long_titles.len() as f32) * 32.0; // close buttons and spacing
     long_width += 32.0; // new tab button
-    long_width += 80.0; // settings button width reserve
+    long_width += 160.0; // library and settings buttons width reserve
     long_width
 }
 
+/// Entry of the tab bar: either a group header or a tab, identified by id.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum TabBarItem {
+    Group(Uuid),
+    Tab(Uuid),
+}
+
+/// Lists the visible tab bar entries: ungrouped tabs first, then each group header
+/// followed by its tabs unless the group is collapsed.
+fn build_tab_bar_items(cfg: &Config) -> Vec<TabBarItem> {
+    let is_known_group = |id: &Uuid| cfg.tab_groups.iter().any(|g| g.id == *id);
+    let mut items: Vec<TabBarItem> = cfg
+        .tabs
+        .iter()
+        .filter(|t| !t.group.as_ref().is_some_and(is_known_group))
+        .map(|t| TabBarItem::Tab(t.id))
+        .collect();
+    for group in &cfg.tab_groups {
+        items.push(TabBarItem::Group(group.id));
+        if !group.collapsed {
+            items.extend(
+                cfg.tabs
+                    .iter()
+                    .filter(|t| t.group == Some(group.id))
+                    .map(|t| TabBarItem::Tab(t.id)),
+            );
+        }
+    }
+    items
+}
+
+/// Returns ids of the tabs shown in the tab bar, in display order.
+pub(super) fn visible_tab_ids(cfg: &Config) -> Vec<Uuid> {
+    build_tab_bar_items(cfg)
+        .into_iter()
+        .filter_map(|item| match item {
+            TabBarItem::Tab(id) => Some(id),
+            TabBarItem::Group(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the group of the active tab, if it belongs to an existing group.
+pub(super) fn active_tab_group(cfg: &Config) -> Option<Uuid> {
+    cfg.get_active_tab_index()
+        .and_then(|i| cfg.tabs[i].group)
+        .filter(|id| cfg.tab_groups.iter().any(|g| g.id == *id))
+}
+
+/// Moves a dragged entry. A group header is moved together with its tabs and never lands
+/// in the middle of another group.
+fn move_tab_bar_item(items: &mut Vec<TabBarItem>, from: usize, to: usize) {
+    if let TabBarItem::Group(_) = items[from] {
+        let block_len = 1 + items[from + 1..]
+            .iter()
+            .take_while(|item| matches!(item, TabBarItem::Tab(_)))
+            .count();
+        let block: Vec<TabBarItem> = items.drain(from..from + block_len).collect();
+        let mut insert_at = if to > from {
+            to.saturating_sub(block_len).max(from)
+        } else {
+            to
+        };
+        insert_at = insert_at.min(items.len());
+        while insert_at < items.len() && matches!(items[insert_at], TabBarItem::Tab(_)) {
+            insert_at += 1;
+        }
+        items.splice(insert_at..insert_at, block);
+    } else {
+        shift_vec(from, to, items);
+    }
+}
+
+/// Applies the order of tab bar entries to the config: tabs take the group of the closest
+/// header before them, and tabs of collapsed groups stay right after their header.
+fn apply_tab_bar_items(cfg: &mut Config, items: &[TabBarItem]) {
+    let mut remaining = std::mem::take(&mut cfg.tabs);
+    let mut groups = std::mem::take(&mut cfg.tab_groups);
+    let mut current_group = None;
+    for item in items {
+        match *item {
+            TabBarItem::Group(id) => {
+                current_group = Some(id);
+                if let Some(index) = groups.iter().position(|g| g.id == id) {
+                    let group = groups.remove(index);
+                    let hidden = group.collapsed;
+                    cfg.tab_groups.push(group);
+                    if hidden {
+                        let (grouped, rest) = remaining
+                            .into_iter()
+                            .partition(|t: &TabConfig| t.group == Some(id));
+                        cfg.tabs.extend(grouped);
+                        remaining = rest;
+                    }
+                }
+            }
+            TabBarItem::Tab(id) => {
+                if let Some(index) = remaining.iter().position(|t| t.id == id) {
+                    let mut tab = remaining.remove(index);
+                    tab.group = current_group;
+                    cfg.tabs.push(tab);
+                }
+            }
+        }
+    }
+    cfg.tab_groups.extend(groups);
+    cfg.tabs.extend(remaining);
+}
+
 pub fn tab_bar_ui(cfg: &mut Config, ui: &mut egui::Ui, store_config: &mut bool) {
     #[derive(PartialEq)]
     enum TabAction {
         None,
         Activate(Uuid),
         Duplicate(Uuid),
-        Close(usize),
-        CreateNew,
+        Close(Uuid),
+        CreateNew(Option<Uuid>),
+        CreateGroup(Option<Uuid>),
+        ReopenClosed,
+        MoveToGroup(Uuid, Option<Uuid>),
+        ToggleGroup(Uuid),
+        SetGroupDefaults(Uuid),
+        ClearGroupDefaults(Uuid),
+        DeleteGroup(Uuid),
+        Export(Uuid),
+        OpenLibrary,
         OpenSettings,
     }
 
     egui::Panel::top("tab_bar").show_inside(ui, |ui| {
         let long_titles = build_long_titles(cfg);
         let short_titles = build_short_titles(cfg);
-        let long_titles_width = compute_long_width(ui, &long_titles);
+        let mut bar_titles = long_titles.clone();
+        bar_titles.extend(cfg.tab_groups.iter().map(|g| g.name.clone()));
+        let long_titles_width = compute_long_width(ui, &bar_titles);
         let use_short_titles = match cfg.title_mode {
             crate::config::TitleMode::Adaptive => long_titles_width > ui.available_width(),
             crate::config::TitleMode::Short => true,
@@ -151,58 +287,190 @@ pub fn tab_bar_ui(cfg: &mut Config, ui: &mut egui::Ui, store_config: &mut bool)
         };
 
         let mut action = TabAction::None;
+        let mut group_renamed = false;
+        let active_group = active_tab_group(cfg);
+        let mut items = build_tab_bar_items(cfg);
 
-        let settings_area_width = 100.0; // pixels reserved on the right for Settings
+        let settings_area_width = 180.0; // pixels reserved on the right for Library and Settings
         let left_area_width = (ui.available_width() - settings_area_width).max(0.0);
 
         ui.horizontal(|ui| {
-            // Left area: normal tabs + new tab button
+            // Left area: group headers, tabs + new tab button
             ui.allocate_ui_with_layout(
                 egui::vec2(left_area_width, ui.spacing().interact_size.y),
                 egui::Layout::left_to_right(egui::Align::Center),
                 |ui| {
                     let tabs_count = cfg.tabs.len();
 
-                    dnd(ui, "tabs_dnd").show_vec(&mut cfg.tabs, |ui, item, handle, state| {
-                        let tab_title = if use_short_titles {
-                            &short_titles[state.index]
-                        } else {
-                            &long_titles[state.index]
-                        };
-                        let is_selected = cfg.active_tab == Some(item.id);
-                        handle.show_drag_cursor_on_hover(false).ui(ui, |ui| {
-                            if ui.selectable_label(is_selected, tab_title).clicked() {
-                                if ui.input(|i| i.modifiers.ctrl) {
-                                    action = TabAction::Duplicate(item.id);
-                                } else {
-                                    action = TabAction::Activate(item.id);
-                                }
-                            }
-                            if tabs_count > 1 {
-                                if ui.button("×").on_hover_text("Close tab").clicked() {
-                                    action = TabAction::Close(state.index);
-                                }
-                            }
+                    let response =
+                        dnd(ui, "tabs_dnd").show(items.iter().copied(), |ui, item, handle, _| {
+                            handle
+                                .show_drag_cursor_on_hover(false)
+                                .ui(ui, |ui| match item {
+                                    TabBarItem::Group(group_id) => {
+                                        let Some(group) =
+                                            cfg.tab_groups.iter_mut().find(|g| g.id == group_id)
+                                        else {
+                                            return;
+                                        };
+                                        let arrow = if group.collapsed { "▶" } else { "▼" };
+                                        let response = ui
+                                        .button(egui::RichText::new(format!(
+                                            "{} {}",
+                                            arrow, group.name
+                                        )))
+                                        .on_hover_text(
+                                            "Click to collapse or expand, right-click for options",
+                                        );
+                                        if response.clicked() {
+                                            action = TabAction::ToggleGroup(group_id);
+                                        }
+                                        response.context_menu(|ui| {
+                                            ui.horizontal(|ui| {
+                                                ui.label("Name:");
+                                                group_renamed |= ui
+                                                    .text_edit_singleline(&mut group.name)
+                                                    .changed();
+                                            });
+                                            ui.label(format!(
+                                                "Default engine: {}",
+                                                group
+                                                    .default_engine_path
+                                                    .as_deref()
+                                                    .unwrap_or("<None>")
+                                            ));
+                                            ui.label(format!(
+                                                "Default IWAD: {}",
+                                                group
+                                                    .default_iwad_path
+                                                    .as_deref()
+                                                    .unwrap_or("<None>")
+                                            ));
+                                            ui.separator();
+                                            if ui.button("New tab in group").clicked() {
+                                                action = TabAction::CreateNew(Some(group_id));
+                                            }
+                                            if ui
+                                            .add_enabled(
+                                                cfg.active_tab.is_some(),
+                                                egui::Button::new(
+                                                    "Use active tab's engine and IWAD as defaults",
+                                                ),
+                                            )
+                                            .clicked()
+                                        {
+                                            action = TabAction::SetGroupDefaults(group_id);
+                                        }
+                                            if ui.button("Clear defaults").clicked() {
+                                                action = TabAction::ClearGroupDefaults(group_id);
+                                            }
+                                            if ui.button("Delete group").clicked() {
+                                                action = TabAction::DeleteGroup(group_id);
+                                            }
+                                        });
+                                    }
+                                    TabBarItem::Tab(tab_id) => {
+                                        let Some(index) =
+                                            cfg.tabs.iter().position(|t| t.id == tab_id)
+                                        else {
+                                            return;
+                                        };
+                                        let tab_title = if use_short_titles {
+                                            &short_titles[index]
+                                        } else {
+                                            &long_titles[index]
+                                        };
+                                        let is_selected = cfg.active_tab == Some(tab_id);
+                                        let response = ui.selectable_label(is_selected, tab_title);
+                                        if response.clicked() {
+                                            if ui.input(|i| i.modifiers.ctrl) {
+                                                action = TabAction::Duplicate(tab_id);
+                                            } else {
+                                                action = TabAction::Activate(tab_id);
+                                            }
+                                        }
+                                        response.context_menu(|ui| {
+                                            ui.menu_button("Move to group", |ui| {
+                                                if ui.button("<No group>").clicked() {
+                                                    action = TabAction::MoveToGroup(tab_id, None);
+                                                }
+                                                for group in &cfg.tab_groups {
+                                                    if ui.button(&group.name).clicked() {
+                                                        action = TabAction::MoveToGroup(
+                                                            tab_id,
+                                                            Some(group.id),
+                                                        );
+                                                    }
+                                                }
+                                            });
+                                            if ui.button("New group from tab").clicked() {
+                                                action = TabAction::CreateGroup(Some(tab_id));
+                                            }
+                                            if ui.button("Export...").clicked() {
+                                                action = TabAction::Export(tab_id);
+                                            }
+                                        });
+                                        if tabs_count > 1
+                                            && ui
+                                                .button("×")
+                                                .on_hover_text(
+                                                    "Close tab (keeps the profile in the library)",
+                                                )
+                                                .clicked()
+                                        {
+                                            action = TabAction::Close(tab_id);
+                                        }
+                                    }
+                                });
                         });
-                    });
 
-                    if ui.button("+").on_hover_text("New tab").clicked() {
-                        action = TabAction::CreateNew;
+                    if let Some(update) = response.final_update() {
+                        move_tab_bar_item(&mut items, update.from, update.to);
+                        apply_tab_bar_items(cfg, &items);
+                        *store_config = true;
+                    }
+
+                    let response = ui
+                        .button("+")
+                        .on_hover_text("New tab (right-click to create a group)");
+                    if response.clicked() {
+                        action = TabAction::CreateNew(active_group);
                     }
+                    response.context_menu(|ui| {
+                        if ui.button("New group").clicked() {
+                            action = TabAction::CreateGroup(None);
+                        }
+                        if ui
+                            .add_enabled(
+                                !cfg.recently_closed.is_empty(),
+                                egui::Button::new("Reopen closed tab")
+                                    .shortcut_text(&cfg.keymap.reopen_closed_tab),
+                            )
+                            .clicked()
+                        {
+                            action = TabAction::ReopenClosed;
+                        }
+                    });
                 },
             );
 
             ui.add_space(ui.available_width() - settings_area_width);
 
-            // Right area: Settings button (persistent, no close button)
+            // Right area: Library and Settings buttons (persistent, no close button)
             ui.allocate_ui_with_layout(
                 egui::vec2(settings_area_width, ui.spacing().interact_size.y),
                 egui::Layout::right_to_left(egui::Align::Max),
                 |ui| {
-                    let settings_selected = cfg.active_tab == None;
+                    let settings_selected =
+                        cfg.active_tab.is_none() && cfg.special_page == SpecialPage::Settings;
                     if ui.selectable_label(settings_selected, "SETTINGS").clicked() {
                         action = TabAction::OpenSettings;
                     }
+                    let library_selected =
+                        cfg.active_tab.is_none() && cfg.special_page == SpecialPage::Library;
+                    if ui.selectable_label(library_selected, "LIBRARY").clicked() {
+                        action = TabAction::OpenLibrary;
+                    }
                 },
             );
         });
@@ -211,38 +479,117 @@ pub fn tab_bar_ui(cfg: &mut Config, ui: &mut egui::Ui, store_config: &mut bool)
             TabAction::Activate(id) => {
                 cfg.active_tab = Some(id);
             }
-            TabAction::Close(index) => {
-                let active_tab_index = cfg.get_active_tab_index().unwrap_or(0);
-                cfg.tabs.remove(index);
-                if active_tab_index == index {
-                    if active_tab_index < cfg.tabs.len() - 1 {
-                        cfg.active_tab = cfg.tabs.get(active_tab_index).map(|t| t.id);
-                    } else {
-                        cfg.active_tab = cfg.tabs.last().map(|t| t.id);
+            TabAction::Close(id) => {
+                if let Some(index) = cfg.tabs.iter().position(|t| t.id == id) {
+                    cfg.close_tab(index);
+                }
+            }
+            TabAction::CreateNew(group) => {
+                cfg.new_tab(group);
+            }
+            TabAction::CreateGroup(tab_id) => {
+                let group = TabGroup::new(format!("Group {}", cfg.tab_groups.len() + 1));
+                if let Some(tab) = tab_id.and_then(|id| cfg.tabs.iter_mut().find(|t| t.id == id)) {
+                    tab.group = Some(group.id);
+                }
+                cfg.tab_groups.push(group);
+            }
+            TabAction::ReopenClosed => {
+                cfg.reopen_closed_tab();
+            }
+            TabAction::MoveToGroup(tab_id, group) => {
+                if let Some(tab) = cfg.tabs.iter_mut().find(|t| t.id == tab_id) {
+                    tab.group = group;
+                }
+            }
+            TabAction::ToggleGroup(id) => {
+                let shown = visible_tab_ids(cfg);
+                let mut collapsed = false;
+                if let Some(group) = cfg.tab_groups.iter_mut().find(|g| g.id == id) {
+                    group.collapsed = !group.collapsed;
+                    collapsed = group.collapsed;
+                }
+                // The active tab can't stay hidden: the next shown tab takes over, or else
+                // the previous one.
+                if collapsed && active_tab_group(cfg) == Some(id) {
+                    let still_shown = visible_tab_ids(cfg);
+                    let index = shown
+                        .iter()
+                        .position(|t| Some(*t) == cfg.active_tab)
+                        .unwrap_or(0);
+                    let neighbour = shown[index..]
+                        .iter()
+                        .chain(shown[..index].iter().rev())
+                        .find(|t| still_shown.contains(t));
+                    cfg.active_tab = neighbour.copied();
+                    if cfg.active_tab.is_none() {
+                        cfg.special_page = SpecialPage::Library;
                     }
                 }
             }
-            TabAction::CreateNew => {
-                cfg.tabs.push(TabConfig::default());
-                cfg.active_tab = cfg.tabs.last().map(|t| t.id);
+            TabAction::SetGroupDefaults(id) => {
+                let (engine, iwad) = cfg
+                    .get_active_tab_index()
+                    .map(|i| {
+                        (
+                            cfg.tabs[i].engine_path.clone(),
+                            cfg.tabs[i].iwad_path.clone(),
+                        )
+                    })
+                    .unwrap_or_default();
+                if let Some(group) = cfg.tab_groups.iter_mut().find(|g| g.id == id) {
+                    group.default_engine_path = engine;
+                    group.default_iwad_path = iwad;
+                }
+            }
+            TabAction::ClearGroupDefaults(id) => {
+                if let Some(group) = cfg.tab_groups.iter_mut().find(|g| g.id == id) {
+                    group.default_engine_path = None;
+                    group.default_iwad_path = None;
+                }
+            }
+            TabAction::DeleteGroup(id) => {
+                cfg.delete_tab_group(id);
             }
             TabAction::Duplicate(id) => {
                 let index = cfg.tabs.iter().position(|t| t.id == id).unwrap_or(0);
-                let tab_config = cfg.tabs.get(index).cloned();
-                cfg.tabs.insert(
-                    index + 1,
-                    TabConfig {
-                        id: Uuid::new_v4(),
-                        ..tab_config.unwrap_or_default()
-                    },
-                );
+                let mut tab_config = cfg.tabs.get(index).cloned().unwrap_or_default();
+                tab_config.id = Uuid::new_v4();
+                // The copy starts with no play history.
+                tab_config.playtime_secs = 0;
+                tab_config.last_played = None;
+                tab_config.file_hashes.clear();
+                tab_config.file_stamps.clear();
+                // Demos of the copy go to its own default folder.
+                tab_config.demo_dir = None;
+                // Saves, screenshots and configs of the copy go to its own data folder.
+                tab_config.isolation.dir = None;
+                // A Wine prefix of the original's own becomes one of the copy's own.
+                let old_prefix = profile_prefix(id);
+                let new_prefix = profile_prefix(tab_config.id);
+                for wrapper in &mut tab_config.wrappers {
+                    if env_var(&wrapper.env, "WINEPREFIX").as_deref() == Some(old_prefix.as_str()) {
+                        set_env_var(&mut wrapper.env, "WINEPREFIX", Some(&new_prefix));
+                    }
+                }
+                cfg.tabs.insert(index + 1, tab_config);
+            }
+            TabAction::Export(id) => {
+                export_profiles_dialog(cfg, &[id]);
+            }
+            TabAction::OpenLibrary => {
+                cfg.active_tab = None;
+                cfg.special_page = SpecialPage::Library;
             }
             TabAction::OpenSettings => {
                 cfg.active_tab = None;
+                cfg.special_page = SpecialPage::Settings;
             }
             TabAction::None => {}
         }
 
-        *store_config = action != TabAction::None;
+        if !matches!(action, TabAction::None | TabAction::Export(_)) || group_renamed {
+            *store_config = true;
+        }
     });
 }
@@ -0,0 +1,261 @@
+use crate::config::{ArchiveInput, TabConfig};
+use crate::dirs::cache_dir;
+use crate::files::hash_file;
+use crate::paths::PathResolver;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Check of an archive's extracted files, run in the background.
+#[derive(Clone)]
+enum ArchiveCheck {
+    /// Being hashed or re-extracted.
+    Pending,
+    Done(Result<(), String>),
+}
+
+/// Checks of archives this session, by archive hash, so that missing or changed archives
+/// are not re-hashed every frame.
+static CHECKED_ARCHIVES: OnceLock<Mutex<HashMap<String, ArchiveCheck>>> = OnceLock::new();
+
+/// Extensions of files worth loading from an archive, in load order.
+const PLAYABLE_EXTENSIONS: [&[&str]; 3] = [&["wad"], &["pk3", "pk7"], &["deh", "bex"]];
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip") || e.eq_ignore_ascii_case("7z"))
+}
+
+/// Directory the archive with the given content hash is extracted into.
+pub fn extraction_dir(hash: &str) -> PathBuf {
+    cache_dir().join("archives").join(hash)
+}
+
+/// Input path of a file extracted from an archive. It refers to the cache through the
+/// `${cache}` variable, so it stays valid on machines with a different cache location.
+pub fn archive_input_path(hash: &str, file: &str) -> String {
+    format!("${{cache}}/archives/{}/{}", hash, file.replace('\\', "/"))
+}
+
+/// Returns the path of an input file relative to the extraction directory of the archive
+/// with the given hash, or None if the file doesn't come from that archive.
+pub fn archive_relative_path(input_path: &str, hash: &str) -> Option<String> {
+    let prefix = format!("${{cache}}/archives/{}/", hash);
+    if let Some(relative) = input_path.strip_prefix(&prefix) {
+        return Some(relative.to_owned());
+    }
+    // Inputs added before path variables were supported use absolute cache paths.
+    Path::new(input_path)
+        .strip_prefix(extraction_dir(hash))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+/// Extracts an archive into the cache and lists its playable files in load order:
+/// WADs, then PK3s, then DeHackEd patches.
+pub fn ingest_archive(path: &str, resolver: &PathResolver) -> Result<ArchiveInput, String> {
+    let resolved = resolver.resolve(path);
+    let hash = hash_file(&resolved).map_err(|e| format!("{}: {}", path, e))?;
+    let dir = extraction_dir(&hash);
+    extract(&resolved, &dir).map_err(|e| format!("{}: {}", path, e))?;
+
+    let mut files = Vec::new();
+    list_files(&dir, &dir, &mut files);
+    files.sort_by_key(|f| f.to_lowercase());
+    let mut playable = Vec::new();
+    for extensions in PLAYABLE_EXTENSIONS {
+        playable.extend(
+            files
+                .iter()
+                .filter(|f| {
+                    Path::new(f)
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| extensions.contains(&e.to_lowercase().as_str()))
+                })
+                .cloned(),
+        );
+    }
+    if playable.is_empty() {
+        return Err(format!("{}: no WAD, PK3 or DEH files in archive", path));
+    }
+
+    Ok(ArchiveInput {
+        path: path.to_owned(),
+        hash,
+        files: playable,
+    })
+}
+
+/// Replaces archives among the input paths with their extracted playable files,
+/// remembering the archives in the profile. Returns the expanded paths and the errors of
+/// archives that failed to extract, which are skipped.
+pub fn expand_archive_inputs(
+    tab_config: &mut TabConfig,
+    paths: Vec<String>,
+    resolver: &PathResolver,
+) -> (Vec<String>, Vec<String>) {
+    let mut expanded = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        if !is_archive(Path::new(&path)) {
+            expanded.push(path);
+            continue;
+        }
+        match ingest_archive(&path, resolver) {
+            Ok(archive) => {
+                expanded.extend(
+                    archive
+                        .files
+                        .iter()
+                        .map(|f| archive_input_path(&archive.hash, f)),
+                );
+                tab_config.archives.retain(|a| a.hash != archive.hash);
+                tab_config.archives.push(archive);
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+    (expanded, errors)
+}
+
+/// Re-extracts the archive if any of its files is missing from the cache, e.g. after
+/// the cache was cleared.
+fn ensure_extracted(archive: &ArchiveInput, resolved: &Path) -> Result<(), String> {
+    let dir = extraction_dir(&archive.hash);
+    if archive.files.iter().all(|f| dir.join(f).is_file()) {
+        return Ok(());
+    }
+    let hash = hash_file(resolved).map_err(|e| format!("{}: {}", archive.path, e))?;
+    if hash != archive.hash {
+        return Err(format!(
+            "{}: archive contents changed since it was added",
+            archive.path
+        ));
+    }
+    extract(resolved, &dir).map_err(|e| format!("{}: {}", archive.path, e))
+}
+
+/// Result of `ensure_extracted`, checking each archive only once per session. The first
+/// call starts the check on a background thread; None is returned until it's done.
+pub fn archive_check(
+    archive: &ArchiveInput,
+    resolver: &PathResolver,
+) -> Option<Result<(), String>> {
+    let checked = CHECKED_ARCHIVES.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut checks = checked.lock().unwrap();
+    match checks.get(&archive.hash) {
+        Some(ArchiveCheck::Done(result)) => return Some(result.clone()),
+        Some(ArchiveCheck::Pending) => return None,
+        None => {}
+    }
+    checks.insert(archive.hash.clone(), ArchiveCheck::Pending);
+    let archive = archive.clone();
+    let resolved = resolver.resolve(&archive.path);
+    thread::spawn(move || {
+        let result = ensure_extracted(&archive, &resolved);
+        // Checks cleared in the meantime were for an archive that has since moved.
+        let mut checks = checked.lock().unwrap();
+        if let Some(check @ ArchiveCheck::Pending) = checks.get_mut(&archive.hash) {
+            *check = ArchiveCheck::Done(result);
+        }
+    });
+    None
+}
+
+/// Waits for the checks of the archives, e.g. before launching a game using them.
+/// Returns the errors of the archives that failed.
+pub fn wait_for_archive_checks(archives: &[ArchiveInput], resolver: &PathResolver) -> Vec<String> {
+    let mut errors = Vec::new();
+    for archive in archives {
+        loop {
+            match archive_check(archive, resolver) {
+                Some(Ok(())) => break,
+                Some(Err(err)) => {
+                    errors.push(err);
+                    break;
+                }
+                None => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+    errors
+}
+
+/// Forgets the archive checks of this session, e.g. after archives were relinked.
+pub fn clear_archive_checks() {
+    if let Some(checked) = CHECKED_ARCHIVES.get() {
+        checked.lock().unwrap().clear();
+    }
+}
+
+fn extract(archive: &Path, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let is_7z = archive
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("7z"));
+    if is_7z {
+        sevenz_rust::decompress_file_with_extract_fn(archive, dir, |entry, reader, _| {
+            if entry.is_directory() {
+                return Ok(true);
+            }
+            if let Some(dest) = sanitized_path(dir, entry.name()) {
+                write_entry(&dest, reader).map_err(sevenz_rust::Error::io)?;
+            }
+            Ok(true)
+        })
+        .map_err(io::Error::other)
+    } else {
+        let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index).map_err(io::Error::other)?;
+            if !entry.is_file() {
+                continue;
+            }
+            if let Some(dest) = entry.enclosed_name().map(|name| dir.join(name)) {
+                write_entry(&dest, &mut entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_entry(dest: &Path, reader: &mut dyn io::Read) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    io::copy(reader, &mut File::create(dest)?)?;
+    Ok(())
+}
+
+/// Joins an archive entry name to the directory, rejecting names escaping it.
+fn sanitized_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    let relative = Path::new(name);
+    relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+        .then(|| dir.join(relative))
+}
+
+fn list_files(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            // Resource forks added by macOS archivers are not game data.
+            if entry.file_name() != "__MACOSX" {
+                list_files(root, &path, out);
+            }
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            out.push(relative.to_string_lossy().to_string());
+        }
+    }
+}
@@ -0,0 +1,470 @@
+use crate::archive::{archive_input_path, archive_relative_path, ingest_archive};
+use crate::config::{ArchiveInput, Config, FileStamp, TabConfig};
+use crate::files::hash_file;
+use crate::flatpak::{flatpak_app_id, flatpak_engine_path};
+use crate::paths::PathResolver;
+use crate::relink::LibraryIndex;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+const BUNDLE_FORMAT: &str = "wadlauncher-profiles";
+const BUNDLE_VERSION: u32 = 1;
+/// Name of the bundle description inside zip bundles.
+const ZIP_MANIFEST: &str = "profiles.toml";
+/// Directory of the included files inside zip bundles.
+const ZIP_FILES_DIR: &str = "files";
+
+/// Self-describing set of profiles. Files are described by name, size and hash instead
+/// of local paths, so that they can be found on another machine.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    pub format: String,
+    pub version: u32,
+    pub profiles: Vec<BundleProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<BundleFile>,
+    /// App id of a Flatpak engine, used instead of `engine`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flatpak_engine: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iwad: Option<BundleFile>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub extra_args: String,
+    #[serde(default)]
+    pub inputs: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+    /// For files extracted from an archive, the path inside the archive. The other
+    /// fields then describe the archive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_entry: Option<String>,
+    /// Path of the file inside a zip bundle, if it was included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundled: Option<String>,
+}
+
+/// Outcome of an import, reported to the user.
+pub struct ImportReport {
+    pub profiles: usize,
+    /// Files not found locally. They are kept by name and can be relinked later.
+    pub missing: Vec<String>,
+    /// Files found by name only, or included files damaged in the bundle, with different
+    /// contents than the exported ones.
+    pub mismatched: Vec<String>,
+}
+
+/// File extracted from a zip bundle.
+pub struct BundledFile {
+    pub path: PathBuf,
+    /// False if its contents don't match the recorded hash.
+    pub verified: bool,
+}
+
+/// Returns true if bundles at the path are zip files including the input files.
+pub fn is_zip_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+/// Writes the profiles to a bundle. The format follows the extension: `.json`, `.zip`
+/// (TOML description plus the input files) or TOML otherwise.
+pub fn export_profiles(
+    profiles: &[&TabConfig],
+    resolver: &PathResolver,
+    path: &Path,
+) -> Result<(), String> {
+    let mut missing = Vec::new();
+    let mut describe = |raw: &str, archive_entry: Option<String>| {
+        let resolved = resolver.resolve(raw);
+        let described = fs::metadata(&resolved)
+            .and_then(|m| Ok((m.len(), hash_file(&resolved)?)))
+            .map(|(size, sha256)| BundleFile {
+                name: file_name(&resolved),
+                size,
+                sha256,
+                archive_entry,
+                bundled: None,
+            });
+        if described.is_err() {
+            missing.push(raw.to_owned());
+        }
+        described.ok()
+    };
+
+    let mut bundle = ProfileBundle {
+        format: BUNDLE_FORMAT.to_owned(),
+        version: BUNDLE_VERSION,
+        profiles: Vec::new(),
+    };
+    for profile in profiles {
+        let inputs = profile
+            .input_paths
+            .iter()
+            .filter_map(|input| match profile.archive_for_input(input) {
+                Some(archive) => {
+                    describe(&archive.path, archive_relative_path(input, &archive.hash))
+                }
+                None => describe(input, None),
+            })
+            .collect();
+        bundle.profiles.push(BundleProfile {
+            name: profile.name.clone(),
+            tags: profile.tags.clone(),
+            engine: profile
+                .engine_path
+                .as_deref()
+                .filter(|p| flatpak_app_id(p).is_none())
+                .and_then(|p| describe(p, None)),
+            flatpak_engine: profile
+                .engine_path
+                .as_deref()
+                .and_then(flatpak_app_id)
+                .map(str::to_owned),
+            iwad: profile.iwad_path.as_deref().and_then(|p| describe(p, None)),
+            extra_args: profile.extra_args.clone(),
+            inputs,
+        });
+    }
+    if !missing.is_empty() {
+        missing.sort();
+        missing.dedup();
+        return Err(format!(
+            "Some files are missing, relink them first:\n{}",
+            missing.join("\n")
+        ));
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let result = match extension.as_str() {
+        "json" => serde_json::to_string_pretty(&bundle)
+            .map_err(|e| e.to_string())
+            .and_then(|text| fs::write(path, text).map_err(|e| e.to_string())),
+        "zip" => write_zip_bundle(&mut bundle, profiles, resolver, path),
+        _ => toml::to_string_pretty(&bundle)
+            .map_err(|e| e.to_string())
+            .and_then(|text| fs::write(path, text).map_err(|e| e.to_string())),
+    };
+    result.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Writes a zip with the bundle description and the input files of all profiles.
+/// IWADs and engines are not included.
+fn write_zip_bundle(
+    bundle: &mut ProfileBundle,
+    profiles: &[&TabConfig],
+    resolver: &PathResolver,
+    path: &Path,
+) -> Result<(), String> {
+    let mut zip = zip::ZipWriter::new(File::create(path).map_err(|e| e.to_string())?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    // Zip paths of included files by hash. Same-named files with different contents are
+    // told apart by a hash prefix.
+    let mut included: HashMap<String, String> = HashMap::new();
+    for (profile, bundle_profile) in profiles.iter().zip(bundle.profiles.iter_mut()) {
+        let sources = profile.input_paths.iter().map(|input| {
+            profile
+                .archive_for_input(input)
+                .map(|a| a.path.as_str())
+                .unwrap_or(input)
+        });
+        for (source, file) in sources.zip(bundle_profile.inputs.iter_mut()) {
+            if let Some(zip_path) = included.get(&file.sha256) {
+                file.bundled = Some(zip_path.clone());
+                continue;
+            }
+            let mut zip_path = format!("{}/{}", ZIP_FILES_DIR, file.name);
+            if included.values().any(|p| *p == zip_path) {
+                zip_path = format!("{}/{}-{}", ZIP_FILES_DIR, &file.sha256[..8], file.name);
+            }
+            zip.start_file(zip_path.as_str(), options)
+                .map_err(|e| e.to_string())?;
+            let mut source = File::open(resolver.resolve(source)).map_err(|e| e.to_string())?;
+            std::io::copy(&mut source, &mut zip).map_err(|e| e.to_string())?;
+            included.insert(file.sha256.clone(), zip_path.clone());
+            file.bundled = Some(zip_path);
+        }
+    }
+    let manifest = toml::to_string_pretty(bundle).map_err(|e| e.to_string())?;
+    zip.start_file(ZIP_MANIFEST, options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(manifest.as_bytes())
+        .map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reads a bundle and adds its profiles as new tabs, resolving files against the files
+/// included in a zip bundle (extracted into `extract_dir`) and the library folders.
+pub fn import_profiles(
+    cfg: &mut Config,
+    path: &Path,
+    extract_dir: Option<&Path>,
+) -> Result<ImportReport, String> {
+    let (bundle, bundled_files) =
+        read_bundle(path, extract_dir).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (profiles, report) = bundle_to_profiles(cfg, bundle, &bundled_files);
+    if let Some(first) = profiles.first() {
+        cfg.active_tab = Some(first.id);
+    }
+    cfg.tabs.extend(profiles);
+    Ok(report)
+}
+
+/// Parses the bundle. Files included in a zip bundle are extracted and returned by hash.
+pub fn read_bundle(
+    path: &Path,
+    extract_dir: Option<&Path>,
+) -> Result<(ProfileBundle, HashMap<String, BundledFile>), String> {
+    let mut bundled_files = HashMap::new();
+    let bundle: ProfileBundle = if is_zip_bundle(path) {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut zip = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        let mut manifest = String::new();
+        zip.by_name(ZIP_MANIFEST)
+            .map_err(|e| format!("{}: {}", ZIP_MANIFEST, e))?
+            .read_to_string(&mut manifest)
+            .map_err(|e| e.to_string())?;
+        let bundle: ProfileBundle = toml::from_str(&manifest).map_err(|e| e.to_string())?;
+        check_bundle(&bundle)?;
+        if let Some(extract_dir) = extract_dir {
+            extract_bundled_files(&bundle, &mut zip, extract_dir, &mut bundled_files)?;
+        }
+        bundle
+    } else {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let is_json = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        if is_json {
+            serde_json::from_str(&text).map_err(|e| e.to_string())?
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())?
+        }
+    };
+    check_bundle(&bundle)?;
+    Ok((bundle, bundled_files))
+}
+
+/// Checks the bundle's format and version, and that its file hashes are well-formed.
+fn check_bundle(bundle: &ProfileBundle) -> Result<(), String> {
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("not a profile bundle".to_owned());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "bundle version {} is not supported, update the launcher",
+            bundle.version
+        ));
+    }
+    let files = bundle
+        .profiles
+        .iter()
+        .flat_map(|p| p.engine.iter().chain(p.iwad.iter()).chain(p.inputs.iter()));
+    for file in files {
+        let is_sha256 =
+            file.sha256.len() == 64 && file.sha256.bytes().all(|b| b.is_ascii_hexdigit());
+        if !is_sha256 {
+            return Err(format!("{}: invalid hash \"{}\"", file.name, file.sha256));
+        }
+    }
+    Ok(())
+}
+
+/// Name to extract a bundled file as, None for names with folders or that escape the
+/// extraction folder.
+fn extract_name(name: &str) -> Option<&OsStr> {
+    let path = Path::new(name);
+    let escapes = path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if name.is_empty() || escapes {
+        return None;
+    }
+    path.file_name()
+}
+
+fn extract_bundled_files(
+    bundle: &ProfileBundle,
+    zip: &mut zip::ZipArchive<File>,
+    extract_dir: &Path,
+    bundled_files: &mut HashMap<String, BundledFile>,
+) -> Result<(), String> {
+    fs::create_dir_all(extract_dir).map_err(|e| e.to_string())?;
+    let files = bundle.profiles.iter().flat_map(|p| p.inputs.iter());
+    for file in files {
+        let Some(zip_path) = file.bundled.as_ref() else {
+            continue;
+        };
+        if bundled_files.contains_key(&file.sha256) {
+            continue;
+        }
+        let name = extract_name(&file.name)
+            .ok_or_else(|| format!("{}: invalid file name", file.name))?
+            .to_string_lossy();
+        let mut target = extract_dir.join(name.as_ref());
+        if target.exists() && hash_file(&target).ok().as_ref() != Some(&file.sha256) {
+            target = extract_dir.join(format!("{}-{}", &file.sha256[..8], name));
+        }
+        if target.parent() != Some(extract_dir) {
+            return Err(format!("{}: invalid file name", file.name));
+        }
+        if !target.exists() {
+            let mut entry = zip
+                .by_name(zip_path)
+                .map_err(|e| format!("{}: {}", zip_path, e))?;
+            let mut out = File::create(&target).map_err(|e| e.to_string())?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        }
+        let verified = hash_file(&target).ok().as_ref() == Some(&file.sha256);
+        bundled_files.insert(
+            file.sha256.clone(),
+            BundledFile {
+                path: target,
+                verified,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Converts bundle profiles into new profiles with local paths.
+pub fn bundle_to_profiles(
+    cfg: &Config,
+    bundle: ProfileBundle,
+    bundled_files: &HashMap<String, BundledFile>,
+) -> (Vec<TabConfig>, ImportReport) {
+    let resolver = PathResolver::new(cfg);
+    let mut index = LibraryIndex::new(cfg, &resolver);
+    let mut report = ImportReport {
+        profiles: 0,
+        missing: Vec::new(),
+        mismatched: Vec::new(),
+    };
+    let mut profiles = Vec::new();
+
+    for bundle_profile in bundle.profiles {
+        let mut profile = TabConfig {
+            id: Uuid::new_v4(),
+            name: bundle_profile.name,
+            tags: bundle_profile.tags,
+            extra_args: bundle_profile.extra_args,
+            ..TabConfig::default()
+        };
+        let mut resolve = |file: &BundleFile, profile: &mut TabConfig| {
+            if let Some(bundled) = bundled_files.get(&file.sha256) {
+                if !bundled.verified {
+                    report.mismatched.push(file.name.clone());
+                }
+                return resolver.contract(bundled.path.to_string_lossy().to_string());
+            }
+            if let Some(found) = index.find(&file.name, Some(file.size), &file.sha256) {
+                return resolver.contract(found.to_string_lossy().to_string());
+            }
+            if let Some(found) = index.any_by_name(&file.name) {
+                report.mismatched.push(file.name.clone());
+                return resolver.contract(found.to_string_lossy().to_string());
+            }
+            // Keep the file by name, with its hash so that relinking finds it later.
+            report.missing.push(file.name.clone());
+            profile
+                .file_hashes
+                .insert(file.name.clone(), file.sha256.clone());
+            profile.file_stamps.insert(
+                file.name.clone(),
+                FileStamp {
+                    size: file.size,
+                    modified: None,
+                },
+            );
+            file.name.clone()
+        };
+
+        let engine_path = bundle_profile
+            .engine
+            .as_ref()
+            .map(|f| resolve(f, &mut profile));
+        let iwad_path = bundle_profile
+            .iwad
+            .as_ref()
+            .map(|f| resolve(f, &mut profile));
+        profile.engine_path = engine_path.or_else(|| {
+            bundle_profile
+                .flatpak_engine
+                .as_deref()
+                .map(flatpak_engine_path)
+        });
+        profile.iwad_path = iwad_path;
+        // Archives by bundle hash, resolved once per profile.
+        let mut archives: HashMap<String, ArchiveInput> = HashMap::new();
+        let mut missing_entries = Vec::new();
+        for file in &bundle_profile.inputs {
+            let Some(entry) = file.archive_entry.as_ref() else {
+                let path = resolve(file, &mut profile);
+                profile.input_paths.push(path);
+                continue;
+            };
+            if !archives.contains_key(&file.sha256) {
+                let path = resolve(file, &mut profile);
+                // A found archive is extracted now. A missing one is recorded as exported,
+                // and gets extracted once relinked.
+                let archive = ingest_archive(&path, &resolver).unwrap_or(ArchiveInput {
+                    path,
+                    hash: file.sha256.clone(),
+                    files: Vec::new(),
+                });
+                archives.insert(file.sha256.clone(), archive);
+            }
+            let archive = archives.get_mut(&file.sha256).unwrap();
+            let entry = entry.replace('\\', "/");
+            if archive.hash == file.sha256 && !archive.files.contains(&entry) {
+                archive.files.push(entry.clone());
+            }
+            if archive.files.contains(&entry) {
+                profile
+                    .input_paths
+                    .push(archive_input_path(&archive.hash, &entry));
+            } else {
+                missing_entries.push(format!("{} › {}", file.name, entry));
+            }
+        }
+        report.missing.extend(missing_entries);
+        profile.archives = archives.into_values().collect();
+        profile.prune_archives();
+        profiles.push(profile);
+    }
+
+    report.profiles = profiles.len();
+    report.missing.sort();
+    report.missing.dedup();
+    report.mismatched.sort();
+    report.mismatched.dedup();
+    (profiles, report)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
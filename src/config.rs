@@ -1,9 +1,138 @@
+use std::collections::{BTreeMap, HashSet};
 use std::hash::Hash;
 
 use serde_derive::{Deserialize, Serialize};
+
+use crate::archive::archive_relative_path;
+use crate::config_file::CURRENT_CONFIG_VERSION;
+use crate::keymap::Keymap;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Archive added as input, extracted into the cache. Its extracted files are listed in
+/// `TabConfig::input_paths`, and are re-extracted from the archive when missing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveInput {
+    pub path: String,
+    /// SHA-256 of the archive, naming its extraction directory.
+    pub hash: String,
+    /// Playable files, relative to the extraction directory, in load order.
+    pub files: Vec<String>,
+}
+
+/// Size and modification time of a profile file when its content hash was recorded.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Unix timestamp (seconds), None when only the size is known.
+    pub modified: Option<u64>,
+}
+
+/// Program the engine is run through, e.g. `gamescope -f -- <engine>`. Wrappers of a
+/// profile run each other in order, the first one being started.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct Wrapper {
+    pub enabled: bool,
+    /// Program name looked up in `PATH`, or a path.
+    pub executable: String,
+    /// Arguments before the wrapped command, split like a shell would.
+    pub args: String,
+    /// Environment variables as `NAME=value` pairs, set for the whole command.
+    pub env: String,
+}
+
+/// dsda-doom speedrun category. Recordings get the skill and game flags of the category,
+/// the rest is checked by `-analysis` after the run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RunCategory {
+    #[default]
+    UvMax,
+    UvSpeed,
+    NmSpeed,
+    Nm100,
+    UvFast,
+    UvRespawn,
+    NoMo,
+    NoMo100,
+    Pacifist,
+    Tyson,
+}
+
+/// dsda-doom speedrun mode of a profile, used when recording and playing demos.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct SpeedrunSettings {
+    pub enabled: bool,
+    pub category: RunCategory,
+    /// Writes the text file DSDA submissions need next to the demo.
+    pub export_text_file: bool,
+    /// Tool-assisted run: strict mode is off, so build mode and other TAS tools work.
+    pub tas: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum NetRole {
+    #[default]
+    Host,
+    Join,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum NetGameMode {
+    #[default]
+    Coop,
+    Deathmatch,
+    AltDeath,
+}
+
+/// Netplay settings of a profile. Launching hosts or joins a game instead of starting a
+/// single player one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MultiplayerSettings {
+    pub enabled: bool,
+    pub role: NetRole,
+    /// Players a hosted game is started for.
+    pub players: u8,
+    pub mode: NetGameMode,
+    /// UDP port, 0 for the engine's default.
+    pub port: u16,
+    /// Server to join, `host` or `host:port`.
+    pub address: String,
+    /// Minutes, 0 for none.
+    pub timelimit: u32,
+    /// 0 for none.
+    pub fraglimit: u32,
+}
+
+impl Default for MultiplayerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            role: NetRole::Host,
+            players: 2,
+            mode: NetGameMode::Coop,
+            port: 0,
+            address: String::new(),
+            timelimit: 0,
+            fraglimit: 0,
+        }
+    }
+}
+
+/// Engine data kept in folders of the profile instead of the engine's shared locations.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct DataIsolation {
+    pub saves: bool,
+    pub screenshots: bool,
+    pub config: bool,
+    /// Folder holding the profile's data. None uses a folder of the profile in the data
+    /// directory.
+    pub dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TabConfig {
     pub id: Uuid,
     pub engine_path: Option<String>,
@@ -11,11 +140,40 @@ pub struct TabConfig {
     pub input_paths: Vec<String>,
     pub last_input_dir: Option<String>,
     #[serde(default)]
-    pub use_mangohud: bool,
+    pub wrappers: Vec<Wrapper>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Unix timestamp (seconds) of the last launch.
     #[serde(default)]
-    pub use_umu_run: bool,
+    pub last_played: Option<u64>,
     #[serde(default)]
-    pub proton_runner: String,
+    pub playtime_secs: u64,
+    #[serde(default)]
+    pub group: Option<Uuid>,
+    #[serde(default)]
+    pub archives: Vec<ArchiveInput>,
+    /// Content hashes of the profile files by path, used to find moved files.
+    #[serde(default)]
+    pub file_hashes: BTreeMap<String, String>,
+    /// Stamps of the files in `file_hashes`, to rehash only changed files and to compare
+    /// sizes before hashing relink candidates.
+    #[serde(default)]
+    pub file_stamps: BTreeMap<String, FileStamp>,
+    /// Additional engine arguments, split like a shell would.
+    #[serde(default)]
+    pub extra_args: String,
+    /// Folder demos are recorded to and listed from. None uses a folder of the profile
+    /// in the data directory.
+    #[serde(default)]
+    pub demo_dir: Option<String>,
+    #[serde(default)]
+    pub speedrun: SpeedrunSettings,
+    #[serde(default)]
+    pub multiplayer: MultiplayerSettings,
+    #[serde(default)]
+    pub isolation: DataIsolation,
 }
 
 impl Hash for TabConfig {
@@ -32,9 +190,20 @@ impl Default for TabConfig {
             iwad_path: None,
             input_paths: Vec::new(),
             last_input_dir: None,
-            use_mangohud: false,
-            use_umu_run: false,
-            proton_runner: "".to_owned(),
+            wrappers: Vec::new(),
+            name: None,
+            tags: Vec::new(),
+            last_played: None,
+            playtime_secs: 0,
+            group: None,
+            archives: Vec::new(),
+            file_hashes: BTreeMap::new(),
+            file_stamps: BTreeMap::new(),
+            extra_args: String::new(),
+            demo_dir: None,
+            speedrun: SpeedrunSettings::default(),
+            multiplayer: MultiplayerSettings::default(),
+            isolation: DataIsolation::default(),
         }
     }
 }
@@ -52,9 +221,126 @@ impl Default for TitleMode {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TabConfig {
+    /// Returns true if the profile matches every whitespace-separated word of the query.
+    /// Name, tags, engine, IWAD and input file names are searched case-insensitively.
+    pub fn matches_search(&self, query: &str) -> bool {
+        let mut haystack = String::new();
+        for part in self
+            .name
+            .iter()
+            .chain(self.tags.iter())
+            .chain(self.engine_path.iter())
+            .chain(self.iwad_path.iter())
+            .chain(self.input_paths.iter())
+        {
+            haystack.push_str(&part.to_lowercase());
+            haystack.push('\n');
+        }
+        for archive in &self.archives {
+            haystack.push_str(&archive.path.to_lowercase());
+            haystack.push('\n');
+        }
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+
+    /// Returns the archive an input file was extracted from.
+    pub fn archive_for_input(&self, input_path: &str) -> Option<&ArchiveInput> {
+        self.archives
+            .iter()
+            .find(|a| archive_relative_path(input_path, &a.hash).is_some())
+    }
+
+    /// Forgets archives none of whose files are used as inputs anymore.
+    pub fn prune_archives(&mut self) {
+        let input_paths = &self.input_paths;
+        self.archives.retain(|a| {
+            input_paths
+                .iter()
+                .any(|p| archive_relative_path(p, &a.hash).is_some())
+        });
+    }
+}
+
+/// Named, collapsible group of tabs in the tab bar.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TabGroup {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Engine inherited by new tabs created in this group.
+    #[serde(default)]
+    pub default_engine_path: Option<String>,
+    /// IWAD inherited by new tabs created in this group.
+    #[serde(default)]
+    pub default_iwad_path: Option<String>,
+}
+
+impl TabGroup {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            collapsed: false,
+            default_engine_path: None,
+            default_iwad_path: None,
+        }
+    }
+}
+
+/// Tab closed from the tab bar, remembered to be reopened.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClosedTab {
+    pub id: Uuid,
+    pub index: usize,
+}
+
+const MAX_RECENTLY_CLOSED: usize = 20;
+
+/// Directory that relative profile paths are resolved against.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RelativePathBase {
+    #[default]
+    ConfigDir,
+    ExecutableDir,
+}
+
+/// Page shown in place of a profile when no tab is active.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SpecialPage {
+    #[default]
+    Settings,
+    Library,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LibrarySort {
+    #[default]
+    LastPlayed,
+    Name,
+    Playtime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
+    /// Schema version, see `config_file::CURRENT_CONFIG_VERSION`. Configs written before
+    /// versioning was introduced have version 0.
+    #[serde(default)]
+    pub version: u32,
+    /// Profiles open as tabs (the working set).
     pub tabs: Vec<TabConfig>,
+    /// Profiles that are stored but not open as tabs.
+    #[serde(default)]
+    pub library: Vec<TabConfig>,
+    #[serde(default)]
+    pub tab_groups: Vec<TabGroup>,
+    /// Recently closed tabs, most recent last.
+    #[serde(default)]
+    pub recently_closed: Vec<ClosedTab>,
     pub active_tab: Option<Uuid>,
     pub last_engine_dir: Option<String>,
     pub last_iwad_dir: Option<String>,
@@ -66,12 +352,33 @@ pub struct Config {
     pub show_command_line: bool,
     #[serde(default)]
     pub show_iwad_in_long_titles: bool,
+    #[serde(default)]
+    pub special_page: SpecialPage,
+    #[serde(default)]
+    pub library_sort: LibrarySort,
+    #[serde(default)]
+    pub keymap: Keymap,
+    /// Folders searched when relinking missing files.
+    #[serde(default)]
+    pub library_dirs: Vec<String>,
+    /// Folders searched for Proton runners besides the Steam, Lutris and Heroic ones.
+    #[serde(default)]
+    pub proton_dirs: Vec<String>,
+    /// User-defined roots usable in paths as `${name}`.
+    #[serde(default)]
+    pub path_variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub relative_path_base: RelativePathBase,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CURRENT_CONFIG_VERSION,
             tabs: vec![TabConfig::default()],
+            library: Vec::new(),
+            tab_groups: Vec::new(),
+            recently_closed: Vec::new(),
             active_tab: None,
             last_engine_dir: None,
             last_iwad_dir: None,
@@ -80,6 +387,13 @@ impl Default for Config {
             title_mode: TitleMode::default(),
             show_command_line: false,
             show_iwad_in_long_titles: false,
+            special_page: SpecialPage::default(),
+            library_sort: LibrarySort::default(),
+            keymap: Keymap::default(),
+            library_dirs: Vec::new(),
+            proton_dirs: Vec::new(),
+            path_variables: BTreeMap::new(),
+            relative_path_base: RelativePathBase::default(),
         }
     }
 }
@@ -100,4 +414,131 @@ impl Config {
             .and_then(|i| self.tabs.get_mut(i))
             .unwrap()
     }
+
+    /// Finds a profile by id, whether it is open as a tab or stored in the library.
+    pub fn find_profile_mut(&mut self, id: Uuid) -> Option<&mut TabConfig> {
+        self.tabs
+            .iter_mut()
+            .chain(self.library.iter_mut())
+            .find(|t| t.id == id)
+    }
+
+    /// Moves a library profile into the tab bar (if not open yet) and activates it.
+    pub fn open_profile(&mut self, id: Uuid) {
+        if let Some(index) = self.library.iter().position(|t| t.id == id) {
+            let profile = self.library.remove(index);
+            self.tabs.push(profile);
+        }
+        if self.tabs.iter().any(|t| t.id == id) {
+            self.active_tab = Some(id);
+        }
+    }
+
+    /// Creates a new tab in the given group, inheriting the group's default engine and IWAD,
+    /// and activates it.
+    pub fn new_tab(&mut self, group: Option<Uuid>) {
+        let group = group.and_then(|id| self.tab_groups.iter().find(|g| g.id == id));
+        let tab = TabConfig {
+            engine_path: group.and_then(|g| g.default_engine_path.clone()),
+            iwad_path: group.and_then(|g| g.default_iwad_path.clone()),
+            group: group.map(|g| g.id),
+            ..TabConfig::default()
+        };
+        self.active_tab = Some(tab.id);
+        self.tabs.push(tab);
+    }
+
+    /// Deletes a tab group. Its tabs are kept and become ungrouped.
+    pub fn delete_tab_group(&mut self, id: Uuid) {
+        self.tab_groups.retain(|g| g.id != id);
+        for tab in self.tabs.iter_mut().chain(self.library.iter_mut()) {
+            if tab.group == Some(id) {
+                tab.group = None;
+            }
+        }
+    }
+
+    /// Closes the tab at the given index, keeping its profile in the library.
+    pub fn close_tab(&mut self, index: usize) {
+        let active_tab_index = self.get_active_tab_index();
+        let profile = self.tabs.remove(index);
+        self.recently_closed.retain(|t| t.id != profile.id);
+        self.recently_closed.push(ClosedTab {
+            id: profile.id,
+            index,
+        });
+        if self.recently_closed.len() > MAX_RECENTLY_CLOSED {
+            self.recently_closed.remove(0);
+        }
+        self.library.insert(0, profile);
+        if active_tab_index == Some(index) {
+            if index < self.tabs.len() {
+                self.active_tab = self.tabs.get(index).map(|t| t.id);
+            } else {
+                self.active_tab = self.tabs.last().map(|t| t.id);
+            }
+        }
+    }
+
+    /// Reopens the most recently closed tab at its former position and activates it.
+    /// Returns false if there is no closed tab left in the library.
+    pub fn reopen_closed_tab(&mut self) -> bool {
+        while let Some(closed) = self.recently_closed.pop() {
+            if let Some(index) = self.library.iter().position(|t| t.id == closed.id) {
+                let profile = self.library.remove(index);
+                self.tabs.insert(closed.index.min(self.tabs.len()), profile);
+                self.active_tab = Some(closed.id);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Permanently deletes a profile stored in the library.
+    pub fn delete_library_profile(&mut self, id: Uuid) {
+        self.library.retain(|t| t.id != id);
+    }
+
+    /// Fixes inconsistencies left by hand edits or older versions: duplicate profile ids,
+    /// and references to profiles or groups that don't exist. Returns descriptions of
+    /// the repairs made.
+    pub fn repair(&mut self) -> Vec<String> {
+        let mut repairs = Vec::new();
+
+        let mut seen = HashSet::new();
+        for tab in self.tabs.iter_mut().chain(self.library.iter_mut()) {
+            if !seen.insert(tab.id) {
+                repairs.push(format!("Profile {} had a duplicate id", tab.id));
+                tab.id = Uuid::new_v4();
+                seen.insert(tab.id);
+            }
+        }
+
+        let mut group_ids = HashSet::new();
+        self.tab_groups.retain(|g| group_ids.insert(g.id));
+        for tab in self.tabs.iter_mut().chain(self.library.iter_mut()) {
+            if let Some(group) = tab.group
+                && !group_ids.contains(&group)
+            {
+                repairs.push(format!("Profile {} referred to a missing group", tab.id));
+                tab.group = None;
+            }
+        }
+
+        let closed_len = self.recently_closed.len();
+        let library_ids: HashSet<Uuid> = self.library.iter().map(|t| t.id).collect();
+        self.recently_closed.retain(|t| library_ids.contains(&t.id));
+        if self.recently_closed.len() != closed_len {
+            repairs.push("Removed recently closed tabs of missing profiles".to_owned());
+        }
+
+        if let Some(id) = self.active_tab
+            && self.get_active_tab_index().is_none()
+        {
+            repairs.push(format!("Active tab {} didn't exist", id));
+            self.active_tab = self.tabs.first().map(|t| t.id);
+        }
+
+        repairs
+    }
 }
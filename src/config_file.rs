@@ -0,0 +1,397 @@
+use crate::config::{Config, Wrapper};
+use crate::dirs::config_file;
+use crate::launch::{GamescopeOptions, join_args, set_env_var};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tinyfiledialogs as tfd;
+use toml::{Table, Value};
+
+/// Version of the config schema written by this build.
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+/// Changes are saved once no further change happened for this long...
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+/// ...but no later than this after the first unsaved change.
+const SAVE_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Migration steps on the raw config table. The step at index `i` upgrades a config
+/// from version `i` to version `i + 1`.
+const MIGRATIONS: [fn(&mut Table); CURRENT_CONFIG_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Versioning was introduced without changing the layout.
+fn migrate_v0_to_v1(_config: &mut Table) {}
+
+/// The `use_mangohud`/`use_umu_run`/`proton_runner`/`gamescope` profile fields were
+/// replaced by the `wrappers` chain, in the order they were composed.
+fn migrate_v1_to_v2(config: &mut Table) {
+    for key in ["tabs", "library"] {
+        let Some(Value::Array(profiles)) = config.get_mut(key) else {
+            continue;
+        };
+        for profile in profiles.iter_mut().filter_map(Value::as_table_mut) {
+            let mut wrappers = Vec::new();
+            if let Some(Value::Table(gamescope)) = profile.remove("gamescope")
+                && gamescope.get("enabled").and_then(Value::as_bool) == Some(true)
+            {
+                let options: GamescopeOptions =
+                    Value::Table(gamescope).try_into().unwrap_or_default();
+                let mut args = options.to_args();
+                args.push("--".to_owned());
+                wrappers.push(Wrapper::from_preset("gamescope", &join_args(&args), ""));
+            }
+            if profile.remove("use_mangohud").and_then(|v| v.as_bool()) == Some(true) {
+                wrappers.push(Wrapper::from_preset("mangohud", "", ""));
+            }
+            let proton_runner = profile
+                .remove("proton_runner")
+                .and_then(|v| v.as_str().map(str::to_owned))
+                .unwrap_or_default();
+            if profile.remove("use_umu_run").and_then(|v| v.as_bool()) == Some(true) {
+                let mut env = String::new();
+                if !proton_runner.is_empty() {
+                    set_env_var(&mut env, "PROTONPATH", Some(&proton_runner));
+                }
+                wrappers.push(Wrapper::from_preset("umu-run", "", &env));
+            }
+            if let Ok(wrappers) = Value::try_from(wrappers) {
+                profile.insert("wrappers".to_owned(), wrappers);
+            }
+        }
+    }
+}
+
+/// Loads the config file, upgrading older schema versions and repairing dangling ids.
+/// If it can't be loaded, offers restoring the latest backup or starting over instead.
+pub fn load_config() -> Config {
+    let path = config_file();
+    match load_config_from(path, true) {
+        Ok(config) => config,
+        Err(err) => recover(path, &err),
+    }
+}
+
+/// Writes the config file atomically: a temp file is written and renamed over the
+/// config, so a crash never leaves a truncated file behind.
+pub fn store_config(config: &Config) -> Result<(), String> {
+    let path = config_file();
+    let text = toml::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp_path = path.with_extension("toml.tmp");
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    };
+    write().map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Saves the config in the background. Changes are marked with `mark_dirty` and written
+/// by `save_if_due` once they settle, so e.g. resizing the window doesn't write on
+/// every frame.
+pub struct ConfigSaver {
+    sender: Option<Sender<Config>>,
+    worker: Option<JoinHandle<()>>,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl ConfigSaver {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("config-saver".to_owned())
+            .spawn(move || save_worker(receiver))
+            .ok();
+        Self {
+            sender: Some(sender),
+            worker,
+            first_change: None,
+            last_change: None,
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        let now = Instant::now();
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
+    }
+
+    /// Hands the config to the background writer if changes have settled. Otherwise
+    /// returns how long to wait before checking again.
+    pub fn save_if_due(&mut self, config: &Config) -> Option<Duration> {
+        let (Some(first_change), Some(last_change)) = (self.first_change, self.last_change) else {
+            return None;
+        };
+        let wait = SAVE_DEBOUNCE
+            .saturating_sub(last_change.elapsed())
+            .min(SAVE_MAX_DELAY.saturating_sub(first_change.elapsed()));
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+        self.save_now(config);
+        None
+    }
+
+    /// Saves pending changes and waits until everything is written, e.g. on exit.
+    pub fn flush(&mut self, config: &Config) {
+        if self.first_change.is_some() {
+            self.save_now(config);
+        }
+        self.sender = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn save_now(&mut self, config: &Config) {
+        self.first_change = None;
+        self.last_change = None;
+        let sent = self
+            .sender
+            .as_ref()
+            .is_some_and(|s| s.send(config.clone()).is_ok());
+        if !sent && let Err(err) = store_config(config) {
+            eprintln!("Failed to save config: {}", err);
+        }
+    }
+}
+
+fn save_worker(receiver: Receiver<Config>) {
+    while let Ok(mut config) = receiver.recv() {
+        // Only the latest of queued snapshots needs to be written.
+        while let Ok(newer) = receiver.try_recv() {
+            config = newer;
+        }
+        if let Err(err) = store_config(&config) {
+            eprintln!("Failed to save config: {}", err);
+        }
+    }
+}
+
+fn load_config_from(path: &Path, backup_before_upgrade: bool) -> Result<Config, String> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut table: Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+    let version = match table.get("version") {
+        None => 0,
+        Some(value) => value
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or("invalid config version")?,
+    };
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "The config was written by a newer version of the launcher \
+             (config version {}, supported up to {}).",
+            version, CURRENT_CONFIG_VERSION
+        ));
+    }
+    if version < CURRENT_CONFIG_VERSION {
+        if backup_before_upgrade {
+            backup(path, "bak").map_err(|e| format!("failed to back up config: {}", e))?;
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut table);
+        }
+        table.insert(
+            "version".to_owned(),
+            toml::Value::Integer(CURRENT_CONFIG_VERSION.into()),
+        );
+    }
+
+    let mut config: Config = table
+        .try_into()
+        .map_err(|e: toml::de::Error| e.to_string())?;
+    for repair in config.repair() {
+        eprintln!("Repaired config: {}", repair);
+    }
+    Ok(config)
+}
+
+/// Asks the user how to continue after the config failed to load.
+fn recover(path: &Path, err: &str) -> Config {
+    let message = format!("Failed to load config {}:\n\n{}", path.display(), err);
+    let restorable = backups(path)
+        .into_iter()
+        .find_map(|p| load_config_from(&p, false).ok().map(|config| (p, config)));
+    if let Some((backup_path, config)) = restorable {
+        let answer = tfd::message_box_yes_no(
+            "wadlauncher",
+            &format!(
+                "{}\n\nRestore the backup {}?\nChoose No to start with an empty config.",
+                message,
+                backup_path.display()
+            ),
+            tfd::MessageBoxIcon::Error,
+            tfd::YesNo::Yes,
+        );
+        if answer == tfd::YesNo::Yes {
+            keep_broken_config(path);
+            store_restored(&config);
+            return config;
+        }
+    }
+    let answer = tfd::message_box_ok_cancel(
+        "wadlauncher",
+        &format!(
+            "{}\n\nStart with an empty config? The current file will be kept as a copy.",
+            message
+        ),
+        tfd::MessageBoxIcon::Error,
+        tfd::OkCancel::Cancel,
+    );
+    if answer == tfd::OkCancel::Cancel {
+        std::process::exit(1);
+    }
+    keep_broken_config(path);
+    let config = Config::default();
+    store_restored(&config);
+    config
+}
+
+fn keep_broken_config(path: &Path) {
+    if let Err(err) = backup(path, "broken") {
+        eprintln!("Failed to keep a copy of the broken config: {}", err);
+    }
+}
+
+fn store_restored(config: &Config) {
+    if let Err(err) = store_config(config) {
+        eprintln!("Failed to store restored config: {}", err);
+    }
+}
+
+/// Copies the file to `<name>.<timestamp>.<suffix>` next to it.
+fn backup(path: &Path, suffix: &str) -> std::io::Result<PathBuf> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup_path = path.with_file_name(format!("{}.{}.{}", file_name, timestamp(), suffix));
+    fs::copy(path, &backup_path)?;
+    Ok(backup_path)
+}
+
+/// Lists backups of the file made before schema upgrades, newest first.
+fn backups(path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", file_name.to_string_lossy());
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".bak"))
+        })
+        .collect();
+    // Timestamps sort lexicographically.
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Current UTC time formatted as `YYYYMMDD-HHMMSS`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_CONFIG: &str = r#"
+config_version = 1
+
+[[tabs]]
+use_mangohud = true
+use_umu_run = true
+proton_runner = "/opt/proton/GE-Proton9"
+
+[tabs.gamescope]
+enabled = true
+output_width = 1920
+output_height = 1080
+upscaler = "Fsr"
+fullscreen = true
+
+[[tabs]]
+use_mangohud = false
+use_umu_run = false
+proton_runner = ""
+
+[tabs.gamescope]
+enabled = false
+output_width = 1280
+
+[[library]]
+use_umu_run = true
+proton_runner = ""
+"#;
+
+    fn wrappers(profile: &Value) -> Vec<Wrapper> {
+        profile["wrappers"].clone().try_into().unwrap()
+    }
+
+    #[test]
+    fn migrates_wrapper_flags_to_wrapper_chain() {
+        let mut config: Table = toml::from_str(V1_CONFIG).unwrap();
+        migrate_v1_to_v2(&mut config);
+
+        let tabs = config["tabs"].as_array().unwrap();
+        let chain = wrappers(&tabs[0]);
+        let executables: Vec<&str> = chain.iter().map(|w| w.executable.as_str()).collect();
+        assert_eq!(executables, ["gamescope", "mangohud", "umu-run"]);
+        assert!(chain.iter().all(|w| w.enabled));
+        assert_eq!(chain[0].args, "-W 1920 -H 1080 -F fsr -f --");
+        assert_eq!(chain[2].env, "PROTONPATH=/opt/proton/GE-Proton9");
+        assert!(wrappers(&tabs[1]).is_empty());
+
+        let library = config["library"].as_array().unwrap();
+        let chain = wrappers(&library[0]);
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].executable, "umu-run");
+        assert_eq!(chain[0].env, "");
+
+        for profile in tabs.iter().chain(library) {
+            let profile = profile.as_table().unwrap();
+            for key in ["gamescope", "use_mangohud", "use_umu_run", "proton_runner"] {
+                assert!(!profile.contains_key(key), "{} was kept", key);
+            }
+        }
+    }
+}
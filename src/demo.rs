@@ -0,0 +1,660 @@
+use crate::config::{Config, TabConfig};
+use crate::engine::{EngineFamily, is_dsda};
+use crate::launch::{build_cmd_with_writable, engine_file_arg, split_args};
+use crate::paths::PathResolver;
+use crate::speedrun::speedrun_args;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// What to do with a demo when launching from the demo panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoAction {
+    Record,
+    Play,
+    /// Plays back as fast as possible and reports the frame rate.
+    Timedemo,
+    /// Like timedemo without rendering, for checking demos quickly.
+    Fastdemo,
+    /// Plays a demo back and continues recording into a new one from where it ends.
+    Continue,
+}
+
+impl DemoAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            DemoAction::Record => "Record",
+            DemoAction::Play => "Play",
+            DemoAction::Timedemo => "Timedemo",
+            DemoAction::Fastdemo => "Fastdemo",
+            DemoAction::Continue => "Continue recording",
+        }
+    }
+
+    /// Returns true if engines of the family can do this.
+    pub fn is_supported(self, family: EngineFamily) -> bool {
+        match self {
+            DemoAction::Record | DemoAction::Play | DemoAction::Timedemo => true,
+            DemoAction::Fastdemo => matches!(family, EngineFamily::Boom | EngineFamily::Eternity),
+            DemoAction::Continue => family == EngineFamily::Boom,
+        }
+    }
+}
+
+/// Demo file found in a profile's demo folder.
+pub struct DemoFile {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+}
+
+/// Raw path of the profile's demo folder, a folder of its own unless set by the user.
+pub fn demo_dir(tab: &TabConfig) -> String {
+    tab.demo_dir
+        .clone()
+        .unwrap_or_else(|| format!("${{data}}/demos/{}", tab.id))
+}
+
+/// Lists the `.lmp` files of a folder, newest first.
+pub fn list_demos(dir: &Path) -> Vec<DemoFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut demos: Vec<DemoFile> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lmp"))
+        })
+        .map(|path| DemoFile {
+            modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+            path,
+        })
+        .collect();
+    demos.sort_by(|a, b| b.modified.cmp(&a.modified).then(a.path.cmp(&b.path)));
+    demos
+}
+
+/// Returns the path for the next recording: `<profile>-<n>.lmp` with the first unused
+/// number, counting up from the highest one in the folder.
+pub fn next_demo_path(tab: &TabConfig, dir: &Path) -> PathBuf {
+    let base: String = tab
+        .name
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or("demo")
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let highest = list_demos(dir)
+        .iter()
+        .filter_map(|demo| {
+            let stem = demo.path.file_stem()?.to_string_lossy().to_string();
+            let number = stem.strip_prefix(&base)?.strip_prefix('-')?;
+            number.parse::<u32>().ok()
+        })
+        .max()
+        .unwrap_or(0);
+    dir.join(format!("{}-{:03}.lmp", base, highest + 1))
+}
+
+/// Builds the command launching the active profile with a demo. For recording, `demo` is
+/// the file to record; otherwise it's the demo to play back.
+pub fn build_demo_cmd(cfg: &Config, action: DemoAction, demo: &Path) -> Option<Command> {
+    let tab = cfg.get_active_tab();
+    let engine_path = tab.engine_path.as_deref()?;
+    if !action.is_supported(EngineFamily::of(engine_path)) {
+        return None;
+    }
+    let dir = PathResolver::new(cfg).resolve(&demo_dir(tab));
+    // Sandboxed engines need access to the demo, and to the demo folder for recordings
+    // and speedrun reports.
+    let mut writable = vec![dir.as_path()];
+    writable.extend(
+        demo.parent()
+            .filter(|parent| parent.is_absolute() && *parent != dir),
+    );
+    let mut cmd = build_cmd_with_writable(cfg, &writable)?;
+    let demo_arg = || engine_file_arg(tab, demo.to_path_buf());
+    match action {
+        // Vanilla-style engines always append `.lmp` to the recording name.
+        DemoAction::Record => cmd
+            .arg("-record")
+            .arg(engine_file_arg(tab, demo.with_extension(""))),
+        DemoAction::Play => cmd.arg("-playdemo").arg(demo_arg()),
+        DemoAction::Timedemo => cmd.arg("-timedemo").arg(demo_arg()),
+        DemoAction::Fastdemo => cmd.arg("-fastdemo").arg(demo_arg()),
+        DemoAction::Continue => cmd
+            .arg("-recordfromto")
+            .arg(demo_arg())
+            .arg(engine_file_arg(tab, next_demo_path(tab, &dir))),
+    };
+    // dsda-doom writes its reports to the working directory, next to the demos.
+    if tab.speedrun.enabled && is_dsda(engine_path) {
+        cmd.args(speedrun_args(&tab.speedrun, action == DemoAction::Record))
+            .current_dir(&dir);
+    }
+    Some(cmd)
+}
+
+/// Game a demo was recorded for. Heretic and Hexen headers can't be told apart from old
+/// Doom ones, so the profile's IWAD decides how a header is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoGame {
+    Doom,
+    /// Doom II and other games with `MAPxx` maps.
+    Doom2,
+    Heretic,
+    Hexen,
+}
+
+impl DemoGame {
+    /// Guesses the game from the IWAD file name.
+    pub fn from_iwad(iwad_path: Option<&str>) -> Self {
+        let name = iwad_path
+            .and_then(|p| Path::new(p).file_stem())
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match name.as_str() {
+            "heretic" | "heretic1" | "blasphem" | "blasphemer" => DemoGame::Heretic,
+            "hexen" | "hexdd" => DemoGame::Hexen,
+            "doom" | "doom1" | "doomu" | "freedoom1" | "chex" => DemoGame::Doom,
+            _ => DemoGame::Doom2,
+        }
+    }
+
+    /// Map name as `ExMy` or `MAPxx`.
+    pub fn map_name(self, episode: u8, map: u8) -> String {
+        match self {
+            DemoGame::Doom | DemoGame::Heretic => format!("E{}M{}", episode, map),
+            DemoGame::Doom2 | DemoGame::Hexen => format!("MAP{:02}", map),
+        }
+    }
+}
+
+/// Settings read from a demo header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoHeader {
+    /// Engine or game version that recorded the demo, e.g. "Doom 1.9" or "MBF".
+    pub format: String,
+    /// PrBoom+ compatibility level the format corresponds to.
+    pub complevel: Option<u8>,
+    /// Skill, 1 to 5.
+    pub skill: u8,
+    /// Map as `ExMy`/`MAPxx`, or the UMAPINFO map name.
+    pub map: String,
+    pub players: usize,
+    pub deathmatch: bool,
+    /// Flags are None in formats that don't store them.
+    pub fast: Option<bool>,
+    pub respawn: Option<bool>,
+    pub no_monsters: Option<bool>,
+    /// Command line stored in the footer by PrBoom+ and dsda-doom, if any.
+    pub footer_args: Vec<String>,
+}
+
+impl DemoHeader {
+    /// Summary like "Boom 2.02 · UV · MAP01 · 1 player · -fast".
+    pub fn summary(&self) -> String {
+        let skill = match self.skill {
+            1 => "ITYTD",
+            2 => "HNTR",
+            3 => "HMP",
+            4 => "UV",
+            5 => "NM",
+            _ => "?",
+        };
+        let mut parts = vec![
+            self.format.clone(),
+            skill.to_owned(),
+            self.map.clone(),
+            if self.players == 1 {
+                "1 player".to_owned()
+            } else {
+                format!("{} players", self.players)
+            },
+        ];
+        if self.deathmatch {
+            parts.push("deathmatch".to_owned());
+        }
+        for (flag, set) in [
+            ("-fast", self.fast),
+            ("-respawn", self.respawn),
+            ("-nomonsters", self.no_monsters),
+        ] {
+            if set == Some(true) {
+                parts.push(flag.to_owned());
+            }
+        }
+        parts.join(" · ")
+    }
+}
+
+type DemoHeaderResult = Result<DemoHeader, String>;
+/// Parsed headers by demo path and modification time.
+type DemoHeaderCache = HashMap<(PathBuf, Option<SystemTime>), DemoHeaderResult>;
+
+static DEMO_HEADERS: OnceLock<Mutex<DemoHeaderCache>> = OnceLock::new();
+
+/// Reads the header of a demo file, cached by path and modification time.
+pub fn read_demo_header(demo: &DemoFile, game: DemoGame) -> DemoHeaderResult {
+    let key = (demo.path.clone(), demo.modified);
+    let cache = DEMO_HEADERS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(header) = cache.lock().unwrap().get(&key) {
+        return header.clone();
+    }
+    let header = fs::read(&demo.path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| parse_demo_header(&data, game));
+    cache.lock().unwrap().insert(key, header.clone());
+    header
+}
+
+/// Boom and MBF headers store options and 32 player slots after the basic settings.
+const BOOM_OPTIONS_SIZE: usize = 64;
+const BOOM_PLAYER_SLOTS: usize = 32;
+
+pub fn parse_demo_header(data: &[u8], game: DemoGame) -> Result<DemoHeader, String> {
+    let truncated = || "demo header is truncated".to_owned();
+    let mut data = data;
+    let mut umapinfo_map = None;
+
+    // PrBoom+ prepends an extension block, e.g. for demos of UMAPINFO maps: the
+    // signature, a version byte, the extension names and then the map of UMAPINFO demos.
+    if data.first() == Some(&255) && data.get(1..6) == Some(b"PR+UM".as_slice()) {
+        let extension_version = *data.get(6).ok_or_else(truncated)?;
+        if extension_version != 1 {
+            return Err(format!(
+                "unknown demo extension version {}",
+                extension_version
+            ));
+        }
+        let count = u16::from_le_bytes([
+            *data.get(7).ok_or_else(truncated)?,
+            *data.get(8).ok_or_else(truncated)?,
+        ]);
+        let mut pos = 9;
+        let mut has_umapinfo = false;
+        for _ in 0..count {
+            let len = *data.get(pos).ok_or_else(truncated)? as usize;
+            let name = data.get(pos + 1..pos + 1 + len).ok_or_else(truncated)?;
+            pos += 1 + len;
+            has_umapinfo |= name == b"UMAPINFO";
+        }
+        if has_umapinfo {
+            let map = data.get(pos..pos + 8).ok_or_else(truncated)?;
+            umapinfo_map = Some(lump_name(map));
+            pos += 8;
+        }
+        data = &data[pos..];
+    }
+
+    let byte = |i: usize| data.get(i).copied().ok_or_else(truncated);
+    let version = byte(0)?;
+    let map_name = |episode: u8, map: u8| game.map_name(episode, map);
+
+    let mut header = match (game, version) {
+        (DemoGame::Hexen, _) => {
+            // Skill, episode, map and 8 slots of player and class.
+            let players = (0..8)
+                .filter(|i| byte(3 + i * 2).is_ok_and(|b| b != 0))
+                .count();
+            DemoHeader::basic("Hexen", byte(0)?, map_name(byte(1)?, byte(2)?), players)
+        }
+        // Versions before 1.4 and Heretic start with the skill.
+        (_, 0..=4) => {
+            let players = (3..7).filter(|i| byte(*i).is_ok_and(|b| b != 0)).count();
+            let format = if game == DemoGame::Heretic {
+                "Heretic"
+            } else {
+                "Doom 1.2"
+            };
+            DemoHeader::basic(format, version, map_name(byte(1)?, byte(2)?), players)
+        }
+        (_, 104..=111) => {
+            let players = (9..13).filter(|i| byte(*i).is_ok_and(|b| b != 0)).count();
+            let format = match version {
+                109 => "Doom 1.9".to_owned(),
+                110 => "Doom 1.9 (Doom 1.10)".to_owned(),
+                111 => "Doom 1.91 (longtics)".to_owned(),
+                _ => format!("Doom 1.{}", version - 100),
+            };
+            let mut header =
+                DemoHeader::basic(&format, byte(1)?, map_name(byte(2)?, byte(3)?), players);
+            header.complevel = Some(match version {
+                104..=106 => 1,
+                _ => 2,
+            });
+            header.deathmatch = byte(4)? != 0;
+            header.respawn = Some(byte(5)? != 0);
+            header.fast = Some(byte(6)? != 0);
+            header.no_monsters = Some(byte(7)? != 0);
+            header
+        }
+        (_, 200..=214 | 221) => {
+            // Version, 6 signature bytes, compatibility flag, then the settings.
+            let signature = data.get(1..7).ok_or_else(truncated)?;
+            let compatibility = byte(7)? != 0;
+            let (format, complevel) = match version {
+                200 | 201 if compatibility => ("Boom 2.01 (compatibility)".to_owned(), 7),
+                200 | 201 => ("Boom 2.01".to_owned(), 8),
+                202 if compatibility => ("Boom 2.02 (compatibility)".to_owned(), 7),
+                202 => ("Boom 2.02".to_owned(), 9),
+                203 if signature.get(1) == Some(&b'B') => ("LxDoom".to_owned(), 10),
+                203 => ("MBF".to_owned(), 11),
+                210 => ("PrBoom 2.2".to_owned(), 13),
+                211 => ("PrBoom 2.3".to_owned(), 14),
+                212 => ("PrBoom 2.4".to_owned(), 15),
+                213 => ("PrBoom 2.5".to_owned(), 16),
+                214 => ("PrBoom+".to_owned(), 17),
+                _ => ("MBF21".to_owned(), 21),
+            };
+            let options = 13;
+            let slots = options + BOOM_OPTIONS_SIZE;
+            let players = (slots..slots + BOOM_PLAYER_SLOTS)
+                .filter(|i| byte(*i).is_ok_and(|b| b != 0))
+                .count();
+            let mut header =
+                DemoHeader::basic(&format, byte(8)?, map_name(byte(9)?, byte(10)?), players);
+            header.complevel = Some(complevel);
+            header.deathmatch = byte(11)? != 0;
+            // MBF21 reorders the options, so its flags aren't read.
+            if version != 221 {
+                header.respawn = Some(byte(options + 6)? != 0);
+                header.fast = Some(byte(options + 7)? != 0);
+                header.no_monsters = Some(byte(options + 8)? != 0);
+            }
+            header
+        }
+        _ => return Err(format!("unknown demo version {}", version)),
+    };
+
+    if let Some(map) = umapinfo_map {
+        header.map = map;
+    }
+    header.footer_args = read_footer_args(data);
+    Ok(header)
+}
+
+impl DemoHeader {
+    /// Header of formats storing only skill (0-based), map and players.
+    fn basic(format: &str, skill: u8, map: String, players: usize) -> Self {
+        Self {
+            format: format.to_owned(),
+            complevel: None,
+            skill: skill + 1,
+            map,
+            players,
+            deathmatch: false,
+            fast: None,
+            respawn: None,
+            no_monsters: None,
+            footer_args: Vec::new(),
+        }
+    }
+}
+
+/// Finds the command line PrBoom+ and dsda-doom store in the demo footer, a small WAD
+/// after the tic data with the parameters as text.
+fn read_footer_args(data: &[u8]) -> Vec<String> {
+    let Some(start) = data.windows(5).rposition(|w| w == b"-iwad") else {
+        return Vec::new();
+    };
+    let end = data[start..]
+        .iter()
+        .position(|b| !(b.is_ascii_graphic() || *b == b' '))
+        .map_or(data.len(), |len| start + len);
+    split_args(&String::from_utf8_lossy(&data[start..end]))
+}
+
+fn lump_name(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|b| **b != 0)
+        .map(|b| (*b as char).to_ascii_uppercase())
+        .collect()
+}
+
+/// Compares a demo with the profile about to play it back. Returns warnings about files
+/// the demo was recorded with that aren't loaded, and about complevel mismatches.
+pub fn demo_mismatches(header: &DemoHeader, tab: &TabConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let file_name = |path: &str| {
+        Path::new(&path.replace('\\', "/"))
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+
+    let demo_args = &header.footer_args;
+    if !demo_args.is_empty() {
+        let mut demo_iwad = None;
+        let mut demo_files = Vec::new();
+        let mut current = "";
+        for arg in demo_args {
+            if arg.starts_with('-') {
+                current = arg.as_str();
+            } else if current == "-iwad" {
+                demo_iwad = Some(file_name(arg));
+            } else if current == "-file" || current == "-deh" {
+                demo_files.push(file_name(arg));
+            }
+        }
+        let profile_iwad = tab.iwad_path.as_deref().map(file_name);
+        if let Some(demo_iwad) = demo_iwad
+            && profile_iwad.as_ref() != Some(&demo_iwad)
+        {
+            warnings.push(format!("Recorded with IWAD {}", demo_iwad));
+        }
+        let profile_files: Vec<String> = tab.input_paths.iter().map(|p| file_name(p)).collect();
+        for file in &demo_files {
+            if !profile_files.contains(file) {
+                warnings.push(format!("Recorded with {}, which isn't loaded", file));
+            }
+        }
+        for file in &profile_files {
+            if !demo_files.contains(file) {
+                warnings.push(format!("{} is loaded, but wasn't when recording", file));
+            }
+        }
+    }
+
+    let profile_complevel = complevel_arg(&split_args(&tab.extra_args));
+    let demo_complevel = complevel_arg(demo_args).or(header.complevel);
+    if let (Some(profile), Some(demo)) = (profile_complevel, demo_complevel)
+        && profile != demo
+    {
+        warnings.push(format!(
+            "Profile uses complevel {}, the demo was recorded with {}",
+            profile, demo
+        ));
+    }
+    warnings
+}
+
+/// Reads the value of `-complevel`/`-cl` from arguments.
+fn complevel_arg(args: &[String]) -> Option<u8> {
+    args.iter()
+        .position(|arg| arg == "-complevel" || arg == "-cl")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Boom/MBF header: version, signature, compatibility, skill, episode, map,
+    /// deathmatch, console player, options, then the player slots.
+    fn boom_header(version: u8, signature: &[u8; 6], players: usize) -> Vec<u8> {
+        let mut data = vec![version];
+        data.extend_from_slice(signature);
+        data.extend([0, 3, 1, 7, 0, 0]);
+        let mut options = [0u8; BOOM_OPTIONS_SIZE];
+        options[7] = 1;
+        data.extend(options);
+        data.extend((0..BOOM_PLAYER_SLOTS).map(|i| u8::from(i < players)));
+        data
+    }
+
+    #[test]
+    fn parses_old_doom_and_heretic_headers() {
+        // Skill, episode, map, 4 player slots.
+        let data = [2, 1, 3, 1, 1, 0, 0];
+        let header = parse_demo_header(&data, DemoGame::Doom).unwrap();
+        assert_eq!(header.format, "Doom 1.2");
+        assert_eq!(header.skill, 3);
+        assert_eq!(header.map, "E1M3");
+        assert_eq!(header.players, 2);
+        assert_eq!(header.complevel, None);
+        assert_eq!(header.fast, None);
+
+        let header = parse_demo_header(&data, DemoGame::Heretic).unwrap();
+        assert_eq!(header.format, "Heretic");
+        assert_eq!(header.map, "E1M3");
+    }
+
+    #[test]
+    fn parses_vanilla_headers() {
+        // Version, skill, episode, map, deathmatch, respawn, fast, nomonsters, console
+        // player, 4 player slots.
+        let data = [109, 3, 1, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0];
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "Doom 1.9");
+        assert_eq!(header.complevel, Some(2));
+        assert_eq!(header.skill, 4);
+        assert_eq!(header.map, "MAP01");
+        assert_eq!(header.players, 3);
+        assert!(header.deathmatch);
+        assert_eq!(header.respawn, Some(false));
+        assert_eq!(header.fast, Some(true));
+        assert_eq!(header.no_monsters, Some(false));
+
+        let data = [105, 3, 2, 4, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+        let header = parse_demo_header(&data, DemoGame::Doom).unwrap();
+        assert_eq!(header.format, "Doom 1.5");
+        assert_eq!(header.complevel, Some(1));
+        assert_eq!(header.map, "E2M4");
+    }
+
+    #[test]
+    fn parses_boom_headers() {
+        let data = boom_header(202, b"\x1dBoom\xe6", 1);
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "Boom 2.02");
+        assert_eq!(header.complevel, Some(9));
+        assert_eq!(header.skill, 4);
+        assert_eq!(header.map, "MAP07");
+        assert_eq!(header.players, 1);
+        assert_eq!(header.fast, Some(true));
+        assert_eq!(header.respawn, Some(false));
+
+        let data = boom_header(203, b"\x1dMBF\xe6\0", 2);
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "MBF");
+        assert_eq!(header.complevel, Some(11));
+        assert_eq!(header.players, 2);
+
+        let data = boom_header(214, b"\x1dBoom\xe6", 1);
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "PrBoom+");
+        assert_eq!(header.complevel, Some(17));
+    }
+
+    #[test]
+    fn parses_mbf21_headers_without_flags() {
+        let data = boom_header(221, b"\x1dMBF\xe6\0", 1);
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "MBF21");
+        assert_eq!(header.complevel, Some(21));
+        assert_eq!(header.fast, None);
+    }
+
+    #[test]
+    fn parses_hexen_headers() {
+        // Skill, episode, map, then player and class of 8 slots.
+        let data = [4, 1, 2, 1, 0, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let header = parse_demo_header(&data, DemoGame::Hexen).unwrap();
+        assert_eq!(header.format, "Hexen");
+        assert_eq!(header.skill, 5);
+        assert_eq!(header.map, "MAP02");
+        assert_eq!(header.players, 2);
+    }
+
+    #[test]
+    fn parses_umapinfo_extension_block() {
+        let mut data = vec![255];
+        data.extend(b"PR+UM");
+        data.push(1);
+        data.extend(1u16.to_le_bytes());
+        data.push(8);
+        data.extend(b"UMAPINFO");
+        data.extend(b"mymap\0\0\0");
+        data.extend(boom_header(214, b"\x1dBoom\xe6", 1));
+        let header = parse_demo_header(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(header.format, "PrBoom+");
+        assert_eq!(header.map, "MYMAP");
+
+        data[6] = 2;
+        assert!(parse_demo_header(&data, DemoGame::Doom2).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_and_truncated_headers() {
+        assert!(parse_demo_header(&[150, 0, 0], DemoGame::Doom2).is_err());
+        assert!(parse_demo_header(&[109, 3], DemoGame::Doom2).is_err());
+        assert!(parse_demo_header(&[255, b'P', b'R', b'+'], DemoGame::Doom2).is_err());
+    }
+
+    #[test]
+    fn reads_footer_args() {
+        let mut data = vec![109, 3, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0x80];
+        data.extend(b"PWAD\0\0\0\0-iwad \"doom2.wad\" -file \"maps/scythe.wad\"\0");
+        assert_eq!(
+            read_footer_args(&data),
+            ["-iwad", "doom2.wad", "-file", "maps/scythe.wad"]
+        );
+        assert!(read_footer_args(&data[..14]).is_empty());
+    }
+
+    #[test]
+    fn warns_about_mismatched_files_and_complevel() {
+        let mut header =
+            parse_demo_header(&boom_header(202, b"\x1dBoom\xe6", 1), DemoGame::Doom2).unwrap();
+        header.footer_args = split_args("-iwad doom2.wad -file scythe.wad -complevel 9");
+        let tab = TabConfig {
+            iwad_path: Some("/games/DOOM2.WAD".to_owned()),
+            input_paths: vec!["/games/scythe.wad".to_owned()],
+            extra_args: "-complevel 9".to_owned(),
+            ..TabConfig::default()
+        };
+        assert!(demo_mismatches(&header, &tab).is_empty());
+
+        let tab = TabConfig {
+            iwad_path: Some("/games/plutonia.wad".to_owned()),
+            input_paths: vec!["/games/av.wad".to_owned()],
+            extra_args: "-cl 11".to_owned(),
+            ..TabConfig::default()
+        };
+        assert_eq!(
+            demo_mismatches(&header, &tab),
+            [
+                "Recorded with IWAD doom2.wad",
+                "Recorded with scythe.wad, which isn't loaded",
+                "av.wad is loaded, but wasn't when recording",
+                "Profile uses complevel 11, the demo was recorded with 9",
+            ]
+        );
+
+        // Without a footer, the complevel of the format is compared.
+        header.footer_args.clear();
+        assert_eq!(
+            demo_mismatches(&header, &tab),
+            ["Profile uses complevel 11, the demo was recorded with 9"]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable overriding the config file location.
+pub const CONFIG_ENV_VAR: &str = "WADLAUNCHER_CONFIG";
+/// File next to the executable that enables portable mode.
+const PORTABLE_MARKER: &str = "wadlauncher.portable";
+/// Config file name used in portable mode.
+const PORTABLE_CONFIG_FILE: &str = "wadlauncher.toml";
+
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+static PORTABLE: OnceLock<bool> = OnceLock::new();
+static EXECUTABLE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("rs", "", "wadlauncher")
+}
+
+/// Chooses the config file once at startup: the `--config` argument, then the
+/// `WADLAUNCHER_CONFIG` environment variable, then the portable config next to the
+/// executable, then the platform config directory.
+pub fn init_config_file(cli_path: Option<PathBuf>) {
+    let path = cli_path
+        .or_else(|| {
+            std::env::var_os(CONFIG_ENV_VAR)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        })
+        .map(|p| std::path::absolute(&p).unwrap_or(p))
+        .unwrap_or_else(default_config_file);
+    let _ = CONFIG_FILE.set(path);
+}
+
+/// Path of the config file.
+pub fn config_file() -> &'static Path {
+    CONFIG_FILE.get_or_init(default_config_file)
+}
+
+fn default_config_file() -> PathBuf {
+    if is_portable() {
+        return executable_dir().join(PORTABLE_CONFIG_FILE);
+    }
+    confy::get_configuration_file_path("wadlauncher", None)
+        .unwrap_or_else(|_| executable_dir().join(PORTABLE_CONFIG_FILE))
+}
+
+/// Returns true if the marker file next to the executable enables portable mode, which
+/// keeps the config and cache next to the executable. Checked once per run.
+pub fn is_portable() -> bool {
+    *PORTABLE.get_or_init(|| executable_dir().join(PORTABLE_MARKER).is_file())
+}
+
+/// Directory for data the launcher can recreate, like extracted archives.
+pub fn cache_dir() -> PathBuf {
+    if is_portable() {
+        return executable_dir().join("cache");
+    }
+    project_dirs()
+        .map(|d| d.cache_dir().to_path_buf())
+        .unwrap_or_else(|| std::env::temp_dir().join("wadlauncher"))
+}
+
+/// Directory for data that can't be recreated, like Wine prefixes.
+pub fn data_dir() -> PathBuf {
+    if is_portable() {
+        return executable_dir().join("data");
+    }
+    project_dirs()
+        .map(|d| d.data_dir().to_path_buf())
+        .unwrap_or_else(config_dir)
+}
+
+/// Directory of the config file.
+pub fn config_dir() -> PathBuf {
+    config_file()
+        .parent()
+        .map(|d| d.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Directory of the launcher executable.
+pub fn executable_dir() -> PathBuf {
+    EXECUTABLE_DIR
+        .get_or_init(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+                .unwrap_or_else(|| PathBuf::from("."))
+        })
+        .clone()
+}
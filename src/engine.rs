@@ -0,0 +1,111 @@
+use crate::flatpak::flatpak_app_id;
+use std::path::Path;
+
+/// Engines sharing command line conventions, recognized by the executable or Flatpak
+/// app name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineFamily {
+    /// GZDoom, LZDoom, VKDoom, Zandronum and other ZDoom descendants.
+    ZDoom,
+    /// PrBoom+, dsda-doom, Woof!, Nugget Doom and other Boom/MBF descendants.
+    Boom,
+    /// Chocolate Doom, Crispy Doom and other vanilla-accurate ports.
+    Chocolate,
+    Eternity,
+    Odamex,
+    Other,
+}
+
+/// Name parts identifying each family, checked in order. Engines without a family of
+/// their own are listed as `Other` so that they're still recognized as engines.
+const FAMILY_KEYWORDS: [(EngineFamily, &[&str]); 6] = [
+    (
+        EngineFamily::ZDoom,
+        &[
+            "gzdoom",
+            "lzdoom",
+            "qzdoom",
+            "vkdoom",
+            "uzdoom",
+            "zandronum",
+            "zdoom",
+        ],
+    ),
+    (
+        EngineFamily::Boom,
+        &[
+            "dsda", "prboom", "glboom", "woof", "nugget", "cherry", "mbf", "boom",
+        ],
+    ),
+    (
+        EngineFamily::Chocolate,
+        &["chocolate", "crispy", "russian", "doomretro"],
+    ),
+    (EngineFamily::Eternity, &["eternity"]),
+    (EngineFamily::Odamex, &["odamex"]),
+    (EngineFamily::Other, &["doomsday"]),
+];
+
+impl EngineFamily {
+    /// Argument setting the savegame folder.
+    pub fn savedir_arg(self) -> Option<&'static str> {
+        match self {
+            EngineFamily::ZDoom | EngineFamily::Chocolate => Some("-savedir"),
+            EngineFamily::Boom => Some("-save"),
+            EngineFamily::Eternity | EngineFamily::Odamex | EngineFamily::Other => None,
+        }
+    }
+
+    /// Argument setting the screenshot folder.
+    pub fn shotdir_arg(self) -> Option<&'static str> {
+        match self {
+            EngineFamily::ZDoom | EngineFamily::Boom => Some("-shotdir"),
+            _ => None,
+        }
+    }
+
+    /// Arguments loading config files, with the extension of their file. Chocolate Doom
+    /// keeps its own settings apart from the vanilla ones.
+    pub fn config_args(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            EngineFamily::ZDoom => &[("-config", "ini")],
+            EngineFamily::Boom | EngineFamily::Odamex => &[("-config", "cfg")],
+            EngineFamily::Chocolate => &[("-config", "cfg"), ("-extraconfig", "extra.cfg")],
+            EngineFamily::Eternity | EngineFamily::Other => &[],
+        }
+    }
+
+    pub fn of(engine_path: &str) -> Self {
+        let name = engine_name(engine_path);
+        FAMILY_KEYWORDS
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| name.contains(k)))
+            .map(|(family, _)| *family)
+            .unwrap_or(EngineFamily::Other)
+    }
+}
+
+/// Returns true if the name, e.g. of a Flatpak app, contains a known engine name.
+pub fn is_engine_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    FAMILY_KEYWORDS
+        .iter()
+        .flat_map(|(_, keywords)| keywords.iter())
+        .any(|k| name.contains(k))
+}
+
+/// Returns true for dsda-doom, which has the speedrun tools other Boom ports lack.
+pub fn is_dsda(engine_path: &str) -> bool {
+    engine_name(engine_path).contains("dsda")
+}
+
+/// Lowercase executable stem, or Flatpak app id, of an engine.
+pub fn engine_name(engine_path: &str) -> String {
+    match flatpak_app_id(engine_path) {
+        Some(app_id) => app_id.to_lowercase(),
+        None => Path::new(engine_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// What a file dropped onto the launcher is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Iwad,
+    Engine,
+    Input,
+}
+
+const INPUT_EXTENSIONS: [&str; 7] = ["wad", "pk3", "pk7", "deh", "bex", "zip", "7z"];
+
+/// Classifies a file by its extension and, for WADs, by the `IWAD`/`PWAD` header magic.
+/// Returns None for files the launcher can't use.
+pub fn classify_file(path: &Path) -> Option<FileKind> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if extension == "wad" && read_magic(path).as_ref() == Some(b"IWAD") {
+        return Some(FileKind::Iwad);
+    }
+    if INPUT_EXTENSIONS.contains(&extension.as_str()) {
+        return Some(FileKind::Input);
+    }
+    if is_executable(path, &extension) {
+        return Some(FileKind::Engine);
+    }
+    None
+}
+
+fn read_magic(path: &Path) -> Option<[u8; 4]> {
+    let mut magic = [0u8; 4];
+    File::open(path).ok()?.read_exact(&mut magic).ok()?;
+    Some(magic)
+}
+
+/// Extensions of native executables. Windows executables are accepted too, to be run
+/// through a wrapper.
+#[cfg(unix)]
+const EXECUTABLE_EXTENSIONS: [&str; 5] = ["", "appimage", "x86_64", "sh", "bin"];
+
+#[cfg(unix)]
+fn is_executable(path: &Path, extension: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    extension == "exe"
+        || (EXECUTABLE_EXTENSIONS.contains(&extension)
+            && path
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0))
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path, extension: &str) -> bool {
+    extension == "exe"
+}
+
+/// Returns the SHA-256 hash of the file contents as a lowercase hex string.
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
@@ -0,0 +1,128 @@
+use crate::engine::is_engine_name;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+
+/// Installed engines, listed on a background thread since `flatpak` can take a while.
+static FLATPAK_ENGINES: Mutex<FlatpakEngines> = Mutex::new(FlatpakEngines {
+    apps: None,
+    listing: false,
+});
+
+struct FlatpakEngines {
+    /// None until first listed.
+    apps: Option<Vec<FlatpakApp>>,
+    listing: bool,
+}
+
+/// Engine paths of Flatpak engines are the app id with this prefix, e.g.
+/// `flatpak:org.zdoom.GZDoom`.
+const ENGINE_PREFIX: &str = "flatpak:";
+
+#[derive(Clone)]
+pub struct FlatpakApp {
+    pub id: String,
+    pub name: String,
+}
+
+/// Returns the app id of a Flatpak engine path.
+pub fn flatpak_app_id(engine_path: &str) -> Option<&str> {
+    engine_path.strip_prefix(ENGINE_PREFIX)
+}
+
+pub fn flatpak_engine_path(app_id: &str) -> String {
+    format!("{}{}", ENGINE_PREFIX, app_id)
+}
+
+/// Returns the cached installed engines, or None until they are known. The first call
+/// starts listing them in the background.
+pub fn installed_flatpak_engines() -> Option<Vec<FlatpakApp>> {
+    let mut engines = FLATPAK_ENGINES.lock().unwrap();
+    if engines.apps.is_none() {
+        start_listing(&mut engines);
+    }
+    engines.apps.clone()
+}
+
+/// Lists the installed engines again in the background, keeping the previous list
+/// until done.
+pub fn refresh_installed_flatpak_engines() {
+    start_listing(&mut FLATPAK_ENGINES.lock().unwrap());
+}
+
+fn start_listing(engines: &mut FlatpakEngines) {
+    if engines.listing {
+        return;
+    }
+    engines.listing = true;
+    thread::spawn(|| {
+        let apps = find_flatpak_engines();
+        let mut engines = FLATPAK_ENGINES.lock().unwrap();
+        engines.apps = Some(apps);
+        engines.listing = false;
+    });
+}
+
+/// Lists installed apps, system-wide and per-user, that look like Doom engines. None are
+/// found if Flatpak isn't installed.
+fn find_flatpak_engines() -> Vec<FlatpakApp> {
+    let Ok(output) = Command::new("flatpak")
+        .args(["list", "--app", "--columns=application,name"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut apps: Vec<FlatpakApp> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (id, name) = line.split_once('\t').unwrap_or((line, line));
+            let id = id.trim();
+            let is_engine = is_engine_name(&format!("{} {}", id, name));
+            (is_engine && !id.is_empty()).then(|| FlatpakApp {
+                id: id.to_owned(),
+                name: name.trim().to_owned(),
+            })
+        })
+        .collect();
+    apps.sort_by(|a, b| a.id.cmp(&b.id));
+    apps.dedup_by(|a, b| a.id == b.id);
+    apps
+}
+
+/// Arguments of `flatpak` running the app, or another program it ships, with read access
+/// to the folders of the given files and write access to `writable_dirs`, which the
+/// sandbox can't see otherwise.
+pub fn flatpak_run_args(
+    app_id: &str,
+    command: Option<&str>,
+    files: &[&Path],
+    writable_dirs: &[&Path],
+) -> Vec<String> {
+    let mut dirs: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    // Subfolders are covered by their parent's permission.
+    let all_dirs = dirs.clone();
+    dirs.retain(|dir| {
+        !all_dirs
+            .iter()
+            .any(|other| other != dir && Path::new(dir).starts_with(other))
+    });
+
+    let mut args = vec!["run".to_owned()];
+    args.extend(dirs.iter().map(|dir| format!("--filesystem={}:ro", dir)));
+    for dir in writable_dirs {
+        args.push(format!("--filesystem={}", dir.display()));
+    }
+    if let Some(command) = command {
+        args.push(format!("--command={}", command));
+    }
+    args.push(app_id.to_owned());
+    args
+}
@@ -0,0 +1,187 @@
+mod doom_launcher;
+mod doom_runner;
+mod zdl;
+
+use crate::archive::{archive_relative_path, expand_archive_inputs};
+use crate::config::{Config, TabConfig};
+use crate::paths::PathResolver;
+use std::path::Path;
+
+/// Launchers whose profiles can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// ZDL `.zdl` files (also its `zdl.ini`).
+    Zdl,
+    /// DoomRunner `options.json` or exported presets.
+    DoomRunner,
+    /// Doom Launcher `DoomLauncher.sqlite` database.
+    DoomLauncher,
+}
+
+impl ImportSource {
+    pub const ALL: [ImportSource; 3] = [
+        ImportSource::Zdl,
+        ImportSource::DoomRunner,
+        ImportSource::DoomLauncher,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImportSource::Zdl => "ZDL",
+            ImportSource::DoomRunner => "DoomRunner",
+            ImportSource::DoomLauncher => "Doom Launcher",
+        }
+    }
+
+    /// File dialog filter for the files of this launcher.
+    pub fn file_filter(self) -> (&'static [&'static str], &'static str) {
+        match self {
+            ImportSource::Zdl => (&["*.zdl", "*.ini"], "ZDL files (*.zdl, zdl.ini)"),
+            ImportSource::DoomRunner => (&["*.json"], "DoomRunner files (*.json)"),
+            ImportSource::DoomLauncher => (
+                &["*.sqlite", "*.db"],
+                "Doom Launcher database (DoomLauncher.sqlite)",
+            ),
+        }
+    }
+}
+
+/// Profile read from another launcher, not yet added to the config.
+#[derive(Debug, Clone)]
+pub struct ImportedProfile {
+    pub profile: TabConfig,
+    /// Names of files inside the input archives to load. Empty loads all playable files.
+    pub archive_files: Vec<String>,
+    /// Source fields that have no equivalent here, as `field = value`.
+    pub unsupported: Vec<String>,
+    /// Whether the profile is selected for import in the preview.
+    pub selected: bool,
+}
+
+impl ImportedProfile {
+    fn new(profile: TabConfig) -> Self {
+        Self {
+            profile,
+            archive_files: Vec::new(),
+            unsupported: Vec::new(),
+            selected: true,
+        }
+    }
+}
+
+/// Profiles read from another launcher, shown for review before they are imported.
+#[derive(Debug, Clone)]
+pub struct ImportPreview {
+    pub source: ImportSource,
+    pub profiles: Vec<ImportedProfile>,
+    /// Problems not tied to a single profile.
+    pub warnings: Vec<String>,
+}
+
+/// Reads the profiles of another launcher from the file.
+pub fn read_import(
+    source: ImportSource,
+    path: &Path,
+    resolver: &PathResolver,
+) -> Result<ImportPreview, String> {
+    let mut preview = ImportPreview {
+        source,
+        profiles: Vec::new(),
+        warnings: Vec::new(),
+    };
+    match source {
+        ImportSource::Zdl => zdl::read(path, &mut preview),
+        ImportSource::DoomRunner => doom_runner::read(path, &mut preview),
+        ImportSource::DoomLauncher => doom_launcher::read(path, &mut preview),
+    }
+    .map_err(|e| format!("{}: {}", path.display(), e))?;
+    for imported in &mut preview.profiles {
+        let profile = &mut imported.profile;
+        for path in profile
+            .engine_path
+            .iter_mut()
+            .chain(profile.iwad_path.iter_mut())
+            .chain(profile.input_paths.iter_mut())
+        {
+            *path = resolver.contract(std::mem::take(path));
+        }
+    }
+    Ok(preview)
+}
+
+/// Adds the selected profiles of the preview as new tabs, extracting their archives.
+/// Returns errors of archives that failed to extract.
+pub fn apply_import(cfg: &mut Config, preview: ImportPreview) -> Vec<String> {
+    let resolver = PathResolver::new(cfg);
+    let mut errors = Vec::new();
+    let mut first_id = None;
+    for imported in preview.profiles.into_iter().filter(|p| p.selected) {
+        let mut profile = imported.profile;
+        let paths = std::mem::take(&mut profile.input_paths);
+        let (expanded, archive_errors) = expand_archive_inputs(&mut profile, paths, &resolver);
+        errors.extend(archive_errors);
+        profile.input_paths = expanded;
+        if !imported.archive_files.is_empty() {
+            let archives = profile.archives.clone();
+            profile.input_paths.retain(|input| {
+                let from_archive = archives
+                    .iter()
+                    .any(|a| archive_relative_path(input, &a.hash).is_some());
+                !from_archive
+                    || imported
+                        .archive_files
+                        .iter()
+                        .any(|f| file_name_lowercase(input) == f.to_lowercase())
+            });
+            profile.prune_archives();
+        }
+        first_id.get_or_insert(profile.id);
+        cfg.tabs.push(profile);
+    }
+    if first_id.is_some() {
+        cfg.active_tab = first_id;
+    }
+    errors
+}
+
+fn file_name_lowercase(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Converts a map given as `MAP07`, `E2M3` or a number to engine arguments.
+fn warp_args(map: &str) -> Vec<String> {
+    let map = map.trim().to_uppercase();
+    if let Some(number) = map.strip_prefix("MAP")
+        && let Ok(number) = number.parse::<u32>()
+    {
+        return vec!["-warp".to_owned(), number.to_string()];
+    }
+    if let Some((episode, mission)) = map.strip_prefix('E').and_then(|m| m.split_once('M'))
+        && let (Ok(episode), Ok(mission)) = (episode.parse::<u32>(), mission.parse::<u32>())
+    {
+        return vec!["-warp".to_owned(), episode.to_string(), mission.to_string()];
+    }
+    if map.parse::<u32>().is_ok() {
+        return vec!["-warp".to_owned(), map];
+    }
+    // Maps with custom names are only supported by ZDoom-based engines.
+    vec!["+map".to_owned(), map]
+}
+
+/// Resolves a path relative to the directory of the imported file.
+fn resolve_relative(base_file: &Path, path: &str) -> String {
+    let path = path.trim();
+    let relative = Path::new(path);
+    // Windows paths like `C:\Doom` are kept as they are on other systems too.
+    let windows_absolute = path.get(1..3).is_some_and(|s| s == ":\\" || s == ":/");
+    if path.is_empty() || relative.is_absolute() || windows_absolute {
+        return path.to_owned();
+    }
+    base_file
+        .parent()
+        .map(|dir| dir.join(relative).to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_owned())
+}
@@ -0,0 +1,235 @@
+use super::{ImportPreview, ImportedProfile, resolve_relative, warp_args};
+use crate::archive::is_archive;
+use crate::config::TabConfig;
+use crate::launch::join_args;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Game file columns that are imported or only describe the file in Doom Launcher.
+const HANDLED_COLUMNS: [&str; 25] = [
+    "GameFileID",
+    "FileName",
+    "Title",
+    "SourcePortID",
+    "IWadID",
+    "SettingsMap",
+    "SettingsSkill",
+    "SettingsExtraParams",
+    "SettingsFiles",
+    "SettingsSpecificFiles",
+    "MinutesPlayed",
+    "Author",
+    "ReleaseDate",
+    "Description",
+    "Map",
+    "MapCount",
+    "Thumbnail",
+    "Comments",
+    "Rating",
+    "Downloaded",
+    "LastPlayed",
+    "FileSizeBytes",
+    "SettingsSaved",
+    "SettingsFilesSourcePort",
+    "SettingsFilesIWAD",
+];
+
+type Row = HashMap<String, Value>;
+
+/// Reads the game files of a Doom Launcher database. Game files are archives in the
+/// configured game file directory; those with launch settings or playtime become
+/// profiles, IWADs are left out.
+pub(super) fn read(path: &Path, preview: &mut ImportPreview) -> Result<(), String> {
+    let db = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| e.to_string())?;
+    let game_file_dir = query(&db, "SELECT Name, Value FROM Configuration")
+        .unwrap_or_default()
+        .into_iter()
+        .find(|row| text(row, "Name") == "GameFileDirectory")
+        .map(|row| resolve_relative(path, &text(&row, "Value")))
+        .unwrap_or_default();
+    let game_file_path = |file_name: &str| {
+        Path::new(&game_file_dir)
+            .join(file_name)
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let source_ports: HashMap<i64, String> = query(&db, "SELECT * FROM SourcePorts")?
+        .into_iter()
+        .map(|row| {
+            let directory = resolve_relative(path, &text(&row, "Directory"));
+            let executable = Path::new(&directory).join(text(&row, "Executable"));
+            (
+                integer(&row, "SourcePortID"),
+                executable.to_string_lossy().to_string(),
+            )
+        })
+        .collect();
+    let game_files = query(&db, "SELECT * FROM GameFiles")?;
+    let file_names: HashMap<i64, String> = game_files
+        .iter()
+        .map(|row| (integer(row, "GameFileID"), text(row, "FileName")))
+        .collect();
+    let iwads: HashMap<i64, i64> = query(&db, "SELECT * FROM IWads")?
+        .into_iter()
+        .map(|row| (integer(&row, "IWadID"), integer(&row, "GameFileID")))
+        .collect();
+    let tags = read_tags(&db);
+
+    for row in &game_files {
+        let id = integer(row, "GameFileID");
+        if iwads.values().any(|game_file_id| *game_file_id == id) {
+            continue;
+        }
+        let has_settings = integer(row, "SourcePortID") > 0
+            || integer(row, "MinutesPlayed") > 0
+            || !text(row, "SettingsExtraParams").is_empty();
+        if !has_settings {
+            continue;
+        }
+
+        let title = text(row, "Title");
+        let mut profile = TabConfig {
+            name: Some(if title.is_empty() {
+                text(row, "FileName")
+            } else {
+                title
+            }),
+            tags: tags.get(&id).cloned().unwrap_or_default(),
+            playtime_secs: integer(row, "MinutesPlayed").max(0) as u64 * 60,
+            ..TabConfig::default()
+        };
+        profile.engine_path = source_ports.get(&integer(row, "SourcePortID")).cloned();
+        profile.iwad_path = iwads
+            .get(&integer(row, "IWadID"))
+            .and_then(|game_file_id| file_names.get(game_file_id))
+            .map(|file_name| game_file_path(file_name));
+        // Additional files are loaded before the game file itself.
+        for file_name in split_list(&text(row, "SettingsFiles")) {
+            profile.input_paths.push(game_file_path(&file_name));
+        }
+        let file_name = text(row, "FileName");
+        let is_game_file = |p: &String| {
+            Path::new(p)
+                .file_name()
+                .is_some_and(|name| name.eq_ignore_ascii_case(file_name.as_str()))
+        };
+        if !profile.input_paths.iter().any(is_game_file) {
+            profile.input_paths.push(game_file_path(&file_name));
+        }
+
+        let mut args = Vec::new();
+        let map = text(row, "SettingsMap");
+        if !map.is_empty() {
+            args.extend(warp_args(&map));
+        }
+        let skill = text(row, "SettingsSkill");
+        if !skill.is_empty() {
+            args.extend(["-skill".to_owned(), skill]);
+        }
+        profile.extra_args = [join_args(&args), text(row, "SettingsExtraParams")]
+            .into_iter()
+            .filter(|args| !args.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut imported = ImportedProfile::new(profile);
+        imported.archive_files = split_list(&text(row, "SettingsSpecificFiles"));
+        let mut columns: Vec<&String> = row.keys().collect();
+        columns.sort();
+        for column in columns {
+            let value = text(row, column);
+            let is_set = !value.is_empty() && value != "0";
+            if is_set && !HANDLED_COLUMNS.contains(&column.as_str()) {
+                imported.unsupported.push(format!("{} = {}", column, value));
+            }
+        }
+        preview.profiles.push(imported);
+    }
+    if preview.profiles.is_empty() {
+        preview
+            .warnings
+            .push("No game files with launch settings or playtime were found".to_owned());
+    }
+    // IWADs stored as archives can't be used directly.
+    let archived_iwads = preview
+        .profiles
+        .iter()
+        .filter_map(|p| p.profile.iwad_path.as_deref())
+        .filter(|iwad| is_archive(Path::new(iwad)))
+        .count();
+    if archived_iwads > 0 {
+        preview.warnings.push(format!(
+            "{} profile(s) use an IWAD stored in an archive; extract it and relink",
+            archived_iwads
+        ));
+    }
+    Ok(())
+}
+
+/// Reads tag names by game file id. Databases without tags yield none.
+fn read_tags(db: &Connection) -> HashMap<i64, Vec<String>> {
+    let names: HashMap<i64, String> = query(db, "SELECT TagID, Name FROM Tags")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| (integer(&row, "TagID"), text(&row, "Name")))
+        .collect();
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in query(db, "SELECT FileID, TagID FROM TagMapping").unwrap_or_default() {
+        if let Some(name) = names.get(&integer(&row, "TagID")) {
+            tags.entry(integer(&row, "FileID"))
+                .or_default()
+                .push(name.clone());
+        }
+    }
+    tags
+}
+
+fn query(db: &Connection, sql: &str) -> Result<Vec<Row>, String> {
+    let mut statement = db.prepare(sql).map_err(|e| e.to_string())?;
+    let columns: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+    let rows = statement
+        .query_map([], |row| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| Ok((column.clone(), row.get::<_, Value>(i)?)))
+                .collect::<rusqlite::Result<Row>>()
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<Row>>>()
+        .map_err(|e| e.to_string())
+}
+
+fn text(row: &Row, column: &str) -> String {
+    match row.get(column) {
+        Some(Value::Text(text)) => text.trim().to_owned(),
+        Some(Value::Integer(value)) => value.to_string(),
+        Some(Value::Real(value)) => value.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn integer(row: &Row, column: &str) -> i64 {
+    match row.get(column) {
+        Some(Value::Integer(value)) => *value,
+        Some(Value::Text(text)) => text.trim().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Splits Doom Launcher's `;`-separated file lists.
+fn split_list(list: &str) -> Vec<String> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
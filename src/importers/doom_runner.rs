@@ -0,0 +1,175 @@
+use super::{ImportPreview, ImportedProfile, resolve_relative, warp_args};
+use crate::config::TabConfig;
+use crate::launch::join_args;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Preset fields that are imported. Field names changed between DoomRunner versions,
+/// so older spellings are accepted too.
+const ENGINE_FIELDS: [&str; 2] = ["selected_engine", "engine"];
+const IWAD_FIELDS: [&str; 3] = ["selected_IWAD", "selected_iwad", "iwad"];
+const MODS_FIELDS: [&str; 2] = ["mods", "mod_list"];
+const ARGS_FIELDS: [&str; 3] = ["cmd_arguments", "cmd_args", "additional_args"];
+/// Preset fields that are ignored without a report, because they only hold UI state.
+const IGNORED_FIELDS: [&str; 3] = ["name", "selected_config", "selected_maps"];
+
+/// Reads DoomRunner's `options.json` or an exported preset file. Engines and IWADs may
+/// be referred to by their name in the `engines`/`IWADs` lists or by path.
+pub(super) fn read(path: &Path, preview: &mut ImportPreview) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let root: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let root = root.as_object().ok_or("not a DoomRunner file")?;
+
+    let engines = named_paths(root.get("engines"), &["executable_path", "path"]);
+    let iwads = named_paths(root.get("IWADs").or_else(|| root.get("iwads")), &["path"]);
+    let presets: Vec<&Map<String, Value>> = match root.get("presets") {
+        Some(Value::Array(presets)) => presets.iter().filter_map(Value::as_object).collect(),
+        _ if root.contains_key("mods") || root.contains_key("selected_engine") => vec![root],
+        _ => return Err("no presets found, not a DoomRunner file".to_owned()),
+    };
+
+    for preset in presets {
+        // Paths in the engine and IWAD lists are relative to the options file too.
+        let lookup = |list: &[(String, String)], value: &str| {
+            let found = list
+                .iter()
+                .find(|(name, entry_path)| name == value || entry_path == value)
+                .map(|(_, entry_path)| entry_path.as_str())
+                .unwrap_or(value);
+            resolve_relative(path, found)
+        };
+        let mut profile = TabConfig {
+            name: string_field(preset, &["name"]).filter(|n| !n.is_empty()),
+            ..TabConfig::default()
+        };
+        profile.engine_path = string_field(preset, &ENGINE_FIELDS)
+            .filter(|v| !v.is_empty())
+            .map(|v| lookup(&engines, &v));
+        profile.iwad_path = string_field(preset, &IWAD_FIELDS)
+            .filter(|v| !v.is_empty())
+            .map(|v| lookup(&iwads, &v));
+        if let Some(Value::Array(mods)) = MODS_FIELDS.iter().find_map(|f| preset.get(*f)) {
+            for entry in mods {
+                let (mod_path, checked) = match entry {
+                    Value::String(mod_path) => (mod_path.as_str(), true),
+                    Value::Object(entry) => (
+                        entry
+                            .get("path")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default(),
+                        entry
+                            .get("checked")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(true),
+                    ),
+                    _ => continue,
+                };
+                if checked && !mod_path.is_empty() {
+                    profile.input_paths.push(resolve_relative(path, mod_path));
+                }
+            }
+        }
+
+        let mut args = Vec::new();
+        let mut unsupported = Vec::new();
+        if let Some(Value::Object(options)) = preset.get("launch_options") {
+            read_launch_options(options, &mut args, &mut unsupported);
+        }
+        let user_args = string_field(preset, &ARGS_FIELDS).unwrap_or_default();
+        profile.extra_args = [join_args(&args), user_args]
+            .into_iter()
+            .filter(|args| !args.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        for (key, value) in preset {
+            let handled = [
+                &ENGINE_FIELDS[..],
+                &IWAD_FIELDS,
+                &MODS_FIELDS,
+                &ARGS_FIELDS,
+                &IGNORED_FIELDS,
+                &["launch_options"],
+            ]
+            .iter()
+            .any(|fields| fields.contains(&key.as_str()));
+            if !handled && !is_empty_value(value) {
+                unsupported.push(format!("{} = {}", key, value));
+            }
+        }
+
+        let mut imported = ImportedProfile::new(profile);
+        imported.unsupported = unsupported;
+        preview.profiles.push(imported);
+    }
+    Ok(())
+}
+
+/// Maps the launch options DoomRunner shares between engines to arguments.
+fn read_launch_options(
+    options: &Map<String, Value>,
+    args: &mut Vec<String>,
+    unsupported: &mut Vec<String>,
+) {
+    for (key, value) in options {
+        match (key.as_str(), value) {
+            ("map", Value::String(map)) if !map.is_empty() => args.extend(warp_args(map)),
+            ("skill", Value::Number(skill)) => {
+                if let Some(skill) = skill.as_u64().filter(|s| (1..=5).contains(s)) {
+                    args.extend(["-skill".to_owned(), skill.to_string()]);
+                }
+            }
+            ("no_monsters", Value::Bool(true)) => args.push("-nomonsters".to_owned()),
+            ("fast_monsters", Value::Bool(true)) => args.push("-fast".to_owned()),
+            ("monsters_respawn", Value::Bool(true)) => args.push("-respawn".to_owned()),
+            // Launch mode and flags default to off.
+            ("mode", _) => {}
+            (_, value) if !is_empty_value(value) => {
+                unsupported.push(format!("launch_options.{} = {}", key, value));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads a DoomRunner list of `{ "name": ..., "path": ... }` entries, which is either an
+/// array or an object holding the array.
+fn named_paths(list: Option<&Value>, path_fields: &[&str]) -> Vec<(String, String)> {
+    let entries = match list {
+        Some(Value::Array(entries)) => entries,
+        Some(Value::Object(object)) => match object.values().find_map(Value::as_array) {
+            Some(entries) => entries,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    entries
+        .iter()
+        .filter_map(Value::as_object)
+        .filter_map(|entry| {
+            let path = string_field(entry, path_fields)?;
+            let name = string_field(entry, &["name"]).unwrap_or_else(|| path.clone());
+            Some((name, path))
+        })
+        .collect()
+}
+
+fn string_field(object: &Map<String, Value>, fields: &[&str]) -> Option<String> {
+    fields
+        .iter()
+        .find_map(|f| object.get(*f).and_then(Value::as_str))
+        .map(str::to_owned)
+}
+
+/// Returns true for values that mean "not set", which aren't worth reporting.
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(b) => !b,
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.values().all(is_empty_value),
+    }
+}
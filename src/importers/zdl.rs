@@ -0,0 +1,154 @@
+use super::{ImportPreview, ImportedProfile, resolve_relative, warp_args};
+use crate::config::TabConfig;
+use crate::launch::join_args;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+type Section = BTreeMap<String, String>;
+
+/// Reads a ZDL save file. The `[zdl.save]` section refers to the engine ("port") and
+/// IWAD by their names in the `[zdl.ports]`/`[zdl.iwads]` lists, which are included in
+/// `zdl.ini` and in files saved with them. Plain paths are accepted too.
+pub(super) fn read(path: &Path, preview: &mut ImportPreview) -> Result<(), String> {
+    // ZDL files may be written in the Windows code page, so decode leniently.
+    let text = String::from_utf8_lossy(&fs::read(path).map_err(|e| e.to_string())?).to_string();
+    let sections = parse_ini(&text);
+    let save = sections
+        .get("zdl.save")
+        .ok_or("no [zdl.save] section, not a ZDL file")?;
+    let mut ports = named_list(sections.get("zdl.ports"), 'p');
+    let mut iwads = named_list(sections.get("zdl.iwads"), 'i');
+    // Names missing from a save file may be listed in the ZDL config next to it.
+    let zdl_ini = path.with_file_name("zdl.ini");
+    if zdl_ini != path
+        && let Ok(bytes) = fs::read(&zdl_ini)
+    {
+        let ini_sections = parse_ini(&String::from_utf8_lossy(&bytes));
+        for (name, port) in named_list(ini_sections.get("zdl.ports"), 'p') {
+            ports.entry(name).or_insert(port);
+        }
+        for (name, iwad) in named_list(ini_sections.get("zdl.iwads"), 'i') {
+            iwads.entry(name).or_insert(iwad);
+        }
+    }
+
+    let mut profile = TabConfig {
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .filter(|_| path.file_name().is_none_or(|n| n != "zdl.ini")),
+        ..TabConfig::default()
+    };
+    let mut unsupported = Vec::new();
+    let mut extra_args = Vec::new();
+    let mut user_args = String::new();
+    let mut files = BTreeMap::new();
+
+    for (key, value) in save {
+        let value = value.trim();
+        match key.as_str() {
+            "port" => {
+                profile.engine_path =
+                    lookup_named(&ports, value, path, "Engine", &mut preview.warnings);
+            }
+            "iwad" => {
+                profile.iwad_path =
+                    lookup_named(&iwads, value, path, "IWAD", &mut preview.warnings);
+            }
+            "skill" => {
+                if value.parse::<u32>().is_ok_and(|skill| skill > 0) {
+                    extra_args.extend(["-skill".to_owned(), value.to_owned()]);
+                }
+            }
+            "warp" => {
+                if !value.is_empty() {
+                    extra_args.extend(warp_args(value));
+                }
+            }
+            "extra" => user_args = value.to_owned(),
+            // Dialog state of ZDL itself.
+            "dlgmode" => {}
+            _ => match key.strip_prefix("file").map(|n| n.parse::<u32>()) {
+                Some(Ok(index)) => {
+                    files.insert(index, resolve_relative(path, value));
+                }
+                _ if !value.is_empty() => unsupported.push(format!("{} = {}", key, value)),
+                _ => {}
+            },
+        }
+    }
+    profile.input_paths = files.into_values().filter(|f| !f.is_empty()).collect();
+    profile.extra_args = [join_args(&extra_args), user_args]
+        .into_iter()
+        .filter(|args| !args.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut imported = ImportedProfile::new(profile);
+    imported.unsupported = unsupported;
+    preview.profiles.push(imported);
+    Ok(())
+}
+
+/// Parses INI text into sections of keys, lowercased. Later keys override earlier ones.
+fn parse_ini(text: &str) -> BTreeMap<String, Section> {
+    let mut sections: BTreeMap<String, Section> = BTreeMap::new();
+    let mut current = String::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with([';', '#']) {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.trim().to_lowercase();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value.to_owned());
+        }
+    }
+    sections
+}
+
+/// Reads a ZDL list of `<prefix><n>n = name` and `<prefix><n>f = path` entries into
+/// paths by name.
+fn named_list(section: Option<&Section>, prefix: char) -> BTreeMap<String, String> {
+    let Some(section) = section else {
+        return BTreeMap::new();
+    };
+    section
+        .iter()
+        .filter_map(|(key, name)| {
+            let index = key.strip_prefix(prefix)?.strip_suffix('n')?;
+            let path = section.get(&format!("{}{}f", prefix, index))?;
+            Some((name.trim().to_owned(), path.trim().to_owned()))
+        })
+        .collect()
+}
+
+fn lookup_named(
+    list: &BTreeMap<String, String>,
+    value: &str,
+    base_file: &Path,
+    what: &str,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Some(path) = list.get(value) {
+        return Some(resolve_relative(base_file, path));
+    }
+    let looks_like_path = value.contains(['/', '\\']) || Path::new(value).extension().is_some();
+    if looks_like_path {
+        return Some(resolve_relative(base_file, value));
+    }
+    warnings.push(format!(
+        "{} \"{}\" is not listed in the file or in zdl.ini next to it",
+        what, value
+    ));
+    None
+}
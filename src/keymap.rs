@@ -0,0 +1,172 @@
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    NextTab,
+    PreviousTab,
+    /// Activates the tab with the given 1-based number.
+    GoToTab(usize),
+    NewTab,
+    CloseTab,
+    ReopenClosedTab,
+    Launch,
+    AddInputFile,
+    RenameProfile,
+    Undo,
+    Redo,
+}
+
+/// Remappable key bindings, stored as text like `Ctrl+Shift+T`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Keymap {
+    pub next_tab: String,
+    pub previous_tab: String,
+    /// Modifiers combined with number keys 1-9 to jump to a tab, e.g. `Ctrl`.
+    pub go_to_tab_modifiers: String,
+    pub new_tab: String,
+    pub close_tab: String,
+    pub reopen_closed_tab: String,
+    pub launch: String,
+    pub add_input_file: String,
+    pub rename_profile: String,
+    pub undo: String,
+    pub redo: String,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            next_tab: "Ctrl+Tab".to_owned(),
+            previous_tab: "Ctrl+Shift+Tab".to_owned(),
+            go_to_tab_modifiers: "Ctrl".to_owned(),
+            new_tab: "Ctrl+T".to_owned(),
+            close_tab: "Ctrl+W".to_owned(),
+            reopen_closed_tab: "Ctrl+Shift+T".to_owned(),
+            launch: "Ctrl+Enter".to_owned(),
+            add_input_file: "Ctrl+O".to_owned(),
+            rename_profile: "F2".to_owned(),
+            undo: "Ctrl+Z".to_owned(),
+            redo: "Ctrl+Y".to_owned(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Lists the remappable bindings with their descriptions in display order, and whether
+    /// the binding consists of modifiers only.
+    pub fn entries_mut(&mut self) -> [(&'static str, bool, &mut String); 11] {
+        [
+            ("Next tab", false, &mut self.next_tab),
+            ("Previous tab", false, &mut self.previous_tab),
+            (
+                "Go to tab 1-9 (modifiers)",
+                true,
+                &mut self.go_to_tab_modifiers,
+            ),
+            ("New tab", false, &mut self.new_tab),
+            ("Close tab", false, &mut self.close_tab),
+            ("Reopen closed tab", false, &mut self.reopen_closed_tab),
+            ("Launch", false, &mut self.launch),
+            ("Add input file", false, &mut self.add_input_file),
+            ("Rename profile", false, &mut self.rename_profile),
+            ("Undo", false, &mut self.undo),
+            ("Redo", false, &mut self.redo),
+        ]
+    }
+
+    /// Returns all valid bindings. Bindings with more modifiers come first, so that
+    /// e.g. `Ctrl+Shift+Tab` is matched before `Ctrl+Tab`.
+    pub fn shortcuts(&self) -> Vec<(ShortcutAction, KeyboardShortcut)> {
+        let mut shortcuts: Vec<(ShortcutAction, KeyboardShortcut)> = [
+            (ShortcutAction::NextTab, &self.next_tab),
+            (ShortcutAction::PreviousTab, &self.previous_tab),
+            (ShortcutAction::NewTab, &self.new_tab),
+            (ShortcutAction::CloseTab, &self.close_tab),
+            (ShortcutAction::ReopenClosedTab, &self.reopen_closed_tab),
+            (ShortcutAction::Launch, &self.launch),
+            (ShortcutAction::AddInputFile, &self.add_input_file),
+            (ShortcutAction::RenameProfile, &self.rename_profile),
+            (ShortcutAction::Undo, &self.undo),
+            (ShortcutAction::Redo, &self.redo),
+        ]
+        .into_iter()
+        .filter_map(|(action, text)| parse_shortcut(text).map(|shortcut| (action, shortcut)))
+        .collect();
+
+        // Bare number keys would fire while typing numbers, so they need a modifier.
+        if let Some(modifiers) = parse_modifiers(&self.go_to_tab_modifiers)
+            && !modifiers.is_none()
+        {
+            const NUM_KEYS: [Key; 9] = [
+                Key::Num1,
+                Key::Num2,
+                Key::Num3,
+                Key::Num4,
+                Key::Num5,
+                Key::Num6,
+                Key::Num7,
+                Key::Num8,
+                Key::Num9,
+            ];
+            for (index, key) in NUM_KEYS.into_iter().enumerate() {
+                shortcuts.push((
+                    ShortcutAction::GoToTab(index + 1),
+                    KeyboardShortcut::new(modifiers, key),
+                ));
+            }
+        }
+
+        shortcuts.sort_by_key(|(_, shortcut)| std::cmp::Reverse(modifier_count(shortcut)));
+        shortcuts
+    }
+}
+
+/// Returns true if the binding text is empty (disabled) or can be parsed.
+/// `modifiers_only` bindings consist of at least one modifier name, and nothing else.
+pub fn is_valid_binding(text: &str, modifiers_only: bool) -> bool {
+    if modifiers_only {
+        text.trim().is_empty() || parse_modifiers(text).is_some_and(|m| !m.is_none())
+    } else {
+        text.trim().is_empty() || parse_shortcut(text).is_some()
+    }
+}
+
+/// Parses a binding like `Ctrl+Shift+T`. Returns None if it is empty or invalid.
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let (modifiers, key) = match text.trim().rsplit_once('+') {
+        Some((modifiers, key)) => (parse_modifiers(modifiers)?, key),
+        None => (Modifiers::NONE, text.trim()),
+    };
+    Key::from_name(key.trim()).map(|key| KeyboardShortcut::new(modifiers, key))
+}
+
+/// Returns true for the function keys F1, F2 and so on.
+pub fn is_function_key(key: Key) -> bool {
+    key.name()
+        .strip_prefix('F')
+        .is_some_and(|number| number.parse::<u8>().is_ok())
+}
+
+/// Parses `+`-separated modifier names like `Ctrl+Shift`.
+pub fn parse_modifiers(text: &str) -> Option<Modifiers> {
+    let mut modifiers = Modifiers::NONE;
+    for name in text.split('+').map(str::trim).filter(|s| !s.is_empty()) {
+        modifiers |= match name.to_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" => Modifiers::COMMAND,
+            "shift" => Modifiers::SHIFT,
+            "alt" | "option" => Modifiers::ALT,
+            _ => return None,
+        };
+    }
+    Some(modifiers)
+}
+
+fn modifier_count(shortcut: &KeyboardShortcut) -> usize {
+    let m = shortcut.modifiers;
+    [m.command || m.ctrl, m.shift, m.alt]
+        .into_iter()
+        .filter(|x| *x)
+        .count()
+}
@@ -0,0 +1,442 @@
+use crate::config::{Config, TabConfig, Wrapper};
+use crate::flatpak::{flatpak_app_id, flatpak_run_args};
+use crate::multiplayer::build_multiplayer_cmds;
+use crate::paths::PathResolver;
+use crate::profile_data::{is_isolated, profile_data_args, profile_data_dir};
+use serde_derive::Deserialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use uuid::Uuid;
+
+/// Builds the command line launching the active profile, or None if it is incomplete.
+/// Path variables and relative paths are expanded.
+pub fn build_cmd(cfg: &Config) -> Option<Command> {
+    build_engine_cmd(cfg, None, true, &[])
+}
+
+/// Like `build_cmd`, also giving sandboxed engines write access to the given folders, e.g.
+/// the one demos are recorded in.
+pub fn build_cmd_with_writable(cfg: &Config, writable_dirs: &[&Path]) -> Option<Command> {
+    build_engine_cmd(cfg, None, true, writable_dirs)
+}
+
+/// Builds the command running a program shipped with the engine, e.g. a game server,
+/// with the profile's wrappers, and files and extra arguments if `with_files` is set.
+pub fn build_companion_cmd(cfg: &Config, program: &str, with_files: bool) -> Option<Command> {
+    build_engine_cmd(cfg, Some(program), with_files, &[])
+}
+
+/// Builds the commands started by launching the active profile: its game, or the
+/// processes of its multiplayer game with the player's own game last.
+pub fn build_launch_cmds(cfg: &Config) -> Vec<Command> {
+    if cfg.get_active_tab().multiplayer.enabled {
+        build_multiplayer_cmds(cfg, None)
+    } else {
+        build_cmd(cfg).into_iter().collect()
+    }
+}
+
+fn build_engine_cmd(
+    cfg: &Config,
+    companion: Option<&str>,
+    with_files: bool,
+    writable_dirs: &[&Path],
+) -> Option<Command> {
+    let tab_config = cfg.get_active_tab();
+    let resolver = PathResolver::new(cfg);
+    if let (Some(engine), Some(iwad)) = (
+        tab_config.engine_path.as_ref(),
+        tab_config.iwad_path.as_ref().map(|p| resolver.resolve(p)),
+    ) {
+        let inputs: Vec<PathBuf> = tab_config
+            .input_paths
+            .iter()
+            .map(|p| resolver.resolve(p))
+            .collect();
+        // Engine data kept in the profile's folder, not for programs like game servers.
+        let data_dir = (companion.is_none() && is_isolated(tab_config))
+            .then(|| resolver.resolve(&profile_data_dir(tab_config)));
+
+        // Wrappers run each other in order, e.g. `gamescope -- mangohud umu-run engine`.
+        let mut program: Vec<OsString> = Vec::new();
+        let mut env = Vec::new();
+        for wrapper in tab_config.wrappers.iter().filter(|w| w.enabled) {
+            if wrapper.executable.trim().is_empty() {
+                continue;
+            }
+            program.push(resolver.resolve_program(wrapper.executable.trim()).into());
+            program.extend(
+                split_args(&wrapper.args)
+                    .iter()
+                    .map(|arg| resolver.expand(arg).into()),
+            );
+            env.extend(parse_env(&wrapper.env));
+        }
+        if let Some(app_id) = flatpak_app_id(engine) {
+            let files: Vec<&Path> = std::iter::once(iwad.as_path())
+                .chain(inputs.iter().map(PathBuf::as_path))
+                .collect();
+            let writable: Vec<&Path> = data_dir
+                .as_deref()
+                .into_iter()
+                .chain(writable_dirs.iter().copied())
+                .collect();
+            program.push("flatpak".into());
+            program.extend(
+                flatpak_run_args(app_id, companion, &files, &writable)
+                    .into_iter()
+                    .map(Into::into),
+            );
+        } else {
+            let engine = resolver.resolve(engine);
+            program.push(match companion {
+                Some(name) => companion_path(&engine, name).into(),
+                None => engine.into(),
+            });
+        }
+
+        let mut cmd = Command::new(&program[0]);
+        cmd.args(&program[1..]);
+        for (name, value) in env {
+            cmd.env(name, resolver.expand(&value));
+        }
+
+        if with_files {
+            cmd.arg("-iwad")
+                .arg(engine_file_arg(tab_config, iwad))
+                .arg("-file")
+                .args(inputs.into_iter().map(|p| engine_file_arg(tab_config, p)));
+            if let Some(dir) = &data_dir {
+                for (arg, path) in profile_data_args(tab_config, dir) {
+                    cmd.arg(arg).arg(engine_file_arg(tab_config, path));
+                }
+            }
+            cmd.args(split_args(&tab_config.extra_args));
+        }
+
+        Some(cmd)
+    } else {
+        None
+    }
+}
+
+/// Path of a program next to the engine, with the engine's extension. Falls back to the
+/// bare name, looked up in `PATH`, when the engine folder doesn't have it.
+fn companion_path(engine: &Path, name: &str) -> PathBuf {
+    let mut file_name = OsString::from(name);
+    if let Some(ext) = engine.extension() {
+        file_name.push(".");
+        file_name.push(ext);
+    }
+    engine
+        .parent()
+        .map(|dir| dir.join(&file_name))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+/// Converts a file argument for the engine. Windows engines under Wine get Windows paths.
+pub fn engine_file_arg(tab: &TabConfig, path: PathBuf) -> OsString {
+    if tab.wrappers.iter().any(|w| w.enabled && w.is_wine()) {
+        wine_path(&path).into()
+    } else {
+        path.into()
+    }
+}
+
+/// Built-in wrappers offered when adding one: name, executable, arguments, environment.
+pub const WRAPPER_PRESETS: [(&str, &str, &str, &str); 9] = [
+    ("GameMode", "gamemoderun", "", ""),
+    ("MangoHud", "mangohud", "", ""),
+    ("gamescope", "gamescope", "-f --", ""),
+    ("umu-run", "umu-run", "", ""),
+    ("Wine", "wine", "", ""),
+    ("Firejail", "firejail", "--noprofile --net=none", ""),
+    ("Bubblewrap", "bwrap", "--dev-bind / / --unshare-net", ""),
+    ("taskset", "taskset", "-c 0-3", ""),
+    ("Custom", "", "", ""),
+];
+
+impl Wrapper {
+    pub fn from_preset(executable: &str, args: &str, env: &str) -> Self {
+        Self {
+            enabled: true,
+            executable: executable.to_owned(),
+            args: args.to_owned(),
+            env: env.to_owned(),
+        }
+    }
+
+    /// Returns true for Wine itself, as opposed to Proton run through umu-run.
+    pub fn is_wine(&self) -> bool {
+        matches!(self.program_name(), "wine" | "wine64")
+    }
+
+    /// File name of the executable, to recognize wrappers with dedicated options.
+    pub fn program_name(&self) -> &str {
+        let executable = self.executable.trim();
+        executable.rsplit(['/', '\\']).next().unwrap_or(executable)
+    }
+}
+
+/// umu-run `STORE` values; protonfixes are looked up by store and `GAMEID`.
+pub const UMU_STORES: [&str; 11] = [
+    "none",
+    "steam",
+    "gog",
+    "egs",
+    "amazon",
+    "humble",
+    "itchio",
+    "ubisoft",
+    "battlenet",
+    "ea",
+    "zoomplatform",
+];
+
+/// umu-run `PROTON_VERB` values useful for launching a game.
+pub const UMU_PROTON_VERBS: [&str; 3] = ["waitforexitandrun", "run", "runinprefix"];
+
+/// Wine prefix of its own for a profile.
+pub fn profile_prefix(profile_id: Uuid) -> String {
+    format!("${{data}}/prefixes/profile-{}", profile_id)
+}
+
+/// Wine prefix shared by the profiles using an engine, named after its executable.
+pub fn engine_prefix(engine_path: &str) -> String {
+    let stem = Path::new(engine_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let name: String = stem
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("${{data}}/prefixes/engine-{}", name)
+}
+
+/// Translates an absolute Linux path to the `Z:` drive Wine maps to the root directory.
+pub fn wine_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if path.starts_with('/') {
+        format!("Z:{}", path.replace('/', "\\"))
+    } else {
+        path.to_string()
+    }
+}
+
+/// Parses `NAME=value` pairs; words without `=` are ignored.
+pub fn parse_env(env: &str) -> Vec<(String, String)> {
+    split_args(env)
+        .into_iter()
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            Some((name.to_owned(), value.to_owned()))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+/// Returns a variable of a `NAME=value` list.
+pub fn env_var(env: &str, name: &str) -> Option<String> {
+    parse_env(env)
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value)
+}
+
+/// Sets a variable in a `NAME=value` list, or removes it if the value is None.
+pub fn set_env_var(env: &mut String, name: &str, value: Option<&str>) {
+    let mut pairs: Vec<String> = parse_env(env)
+        .into_iter()
+        .filter(|(n, _)| n != name)
+        .map(|(n, v)| format!("{}={}", n, v))
+        .collect();
+    if let Some(value) = value {
+        pairs.push(format!("{}={}", name, value));
+    }
+    *env = join_args(&pairs);
+}
+
+/// Upscaling filter gamescope applies when the internal resolution is lower.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+pub enum GamescopeUpscaler {
+    #[default]
+    None,
+    Fsr,
+    Nis,
+}
+
+/// Options of the gamescope wrapper, edited in place of its arguments. Zero sizes and
+/// refresh rate leave gamescope's defaults.
+#[derive(Debug, Clone, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct GamescopeOptions {
+    pub output_width: u32,
+    pub output_height: u32,
+    pub internal_width: u32,
+    pub internal_height: u32,
+    pub refresh_rate: u32,
+    pub upscaler: GamescopeUpscaler,
+    pub fullscreen: bool,
+    pub hdr: bool,
+}
+
+impl GamescopeOptions {
+    /// Reads the options from gamescope arguments. Unknown arguments are returned as
+    /// they are, in order.
+    pub fn from_args(args: &[String]) -> (Self, Vec<String>) {
+        let mut options = Self::default();
+        let mut rest = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut number = |field: &mut u32| {
+                *field = iter.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+            };
+            match arg.as_str() {
+                "-W" | "--output-width" => number(&mut options.output_width),
+                "-H" | "--output-height" => number(&mut options.output_height),
+                "-w" | "--nested-width" => number(&mut options.internal_width),
+                "-h" | "--nested-height" => number(&mut options.internal_height),
+                "-r" | "--nested-refresh" => number(&mut options.refresh_rate),
+                "-F" | "--filter" => {
+                    options.upscaler = match iter.next().map(String::as_str) {
+                        Some("fsr") => GamescopeUpscaler::Fsr,
+                        Some("nis") => GamescopeUpscaler::Nis,
+                        _ => GamescopeUpscaler::None,
+                    }
+                }
+                // Older gamescope versions had a flag per upscaler.
+                "-U" | "--fsr-upscaling" => options.upscaler = GamescopeUpscaler::Fsr,
+                "-Y" | "--nis-upscaling" => options.upscaler = GamescopeUpscaler::Nis,
+                "-f" | "--fullscreen" => options.fullscreen = true,
+                "--hdr-enabled" => options.hdr = true,
+                _ => rest.push(arg.clone()),
+            }
+        }
+        (options, rest)
+    }
+
+    /// Converts the options to gamescope arguments, leaving out unset ones.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut push = |flag: &str, value: u32| {
+            if value > 0 {
+                args.extend([flag.to_owned(), value.to_string()]);
+            }
+        };
+        push("-W", self.output_width);
+        push("-H", self.output_height);
+        push("-w", self.internal_width);
+        push("-h", self.internal_height);
+        push("-r", self.refresh_rate);
+        match self.upscaler {
+            GamescopeUpscaler::None => {}
+            GamescopeUpscaler::Fsr => args.extend(["-F".to_owned(), "fsr".to_owned()]),
+            GamescopeUpscaler::Nis => args.extend(["-F".to_owned(), "nis".to_owned()]),
+        }
+        if self.fullscreen {
+            args.push("-f".to_owned());
+        }
+        if self.hdr {
+            args.push("--hdr-enabled".to_owned());
+        }
+        args
+    }
+}
+
+/// Splits an argument string at whitespace, keeping quoted parts together.
+pub fn split_args(args: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for c in args.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    result.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        result.push(current);
+    }
+    result
+}
+
+/// Joins arguments into a string that `split_args` splits back into them.
+pub fn join_args<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| quote_arg(arg.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quotes an argument if needed, with the quote character it doesn't contain. Arguments
+/// containing both are quoted in parts, `split_args` joins adjacent quoted parts.
+fn quote_arg(arg: &str) -> String {
+    let needs_quotes =
+        arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains(['"', '\'']);
+    if !needs_quotes {
+        return arg.to_owned();
+    }
+    if !arg.contains('"') {
+        return format!("\"{}\"", arg);
+    }
+    if !arg.contains('\'') {
+        return format!("'{}'", arg);
+    }
+    let mut quoted = String::new();
+    for (i, part) in arg.split('"').enumerate() {
+        if i > 0 {
+            quoted.push_str("'\"'");
+        }
+        if !part.is_empty() {
+            quoted.push_str(&format!("\"{}\"", part));
+        }
+    }
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_args_round_trips_through_split_args() {
+        let cases: [&[&str]; 6] = [
+            &["-file", "a.wad", "b.wad"],
+            &["-file", "my maps/a.wad", ""],
+            &["+say", "say \"hi there\""],
+            &["WINEPREFIX=/home/me/it's here"],
+            &["both \"double\" and 'single'", "\"", "'", "\"'\""],
+            &[],
+        ];
+        for args in cases {
+            assert_eq!(split_args(&join_args(args)), args);
+        }
+    }
+
+    #[test]
+    fn set_env_var_keeps_quoted_values() {
+        let mut env = String::new();
+        set_env_var(&mut env, "MSG", Some("say \"hi there\""));
+        set_env_var(&mut env, "OTHER", Some("x"));
+        assert_eq!(env_var(&env, "MSG").as_deref(), Some("say \"hi there\""));
+        assert_eq!(env_var(&env, "OTHER").as_deref(), Some("x"));
+    }
+}
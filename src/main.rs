@@ -1,15 +1,37 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod archive;
+mod bundle;
 mod config;
+mod config_file;
+mod demo;
+mod dirs;
+mod engine;
+mod files;
+mod flatpak;
+#[path = "importers/_mod.rs"]
+mod importers;
+mod keymap;
+mod launch;
+mod multiplayer;
+mod paths;
+mod profile_data;
+mod proton;
+mod relink;
+mod savegame;
+mod speedrun;
 mod wad;
 
 use app::App;
-use arboard::Clipboard;
 use config::Config;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: wadlauncher [--config <path>]";
 
 fn main() {
-    let config: Config = confy::load("wadlauncher", None).unwrap();
+    dirs::init_config_file(parse_args());
+    let config: Config = config_file::load_config();
     let width = config.window_width.unwrap_or(640.0);
     let height = config.window_height.unwrap_or(480.0);
     eframe::run_native(
@@ -18,15 +40,38 @@ fn main() {
             viewport: eframe::egui::ViewportBuilder::default().with_inner_size([width, height]),
             ..Default::default()
         },
-        Box::new(|_| {
-            Ok(Box::new(App {
-                config,
-                clipboard: Clipboard::new().unwrap(),
-                titlepic_texture: None,
-                last_iwad_path: None,
-                last_wad_path: None,
-            }))
-        }),
+        Box::new(|_| Ok(Box::new(App::new(config)))),
     )
     .unwrap();
 }
+
+/// Parses command line arguments, returning the config file path given with `--config`.
+fn parse_args() -> Option<PathBuf> {
+    let mut config_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            config_path = args.next().map(PathBuf::from);
+            if config_path.is_none() {
+                exit_with_usage("--config requires a path");
+            }
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            config_path = Some(PathBuf::from(path));
+        } else if arg == "--help" || arg == "-h" {
+            println!("{}", USAGE);
+            println!(
+                "The config path can also be set with the {} environment variable.",
+                dirs::CONFIG_ENV_VAR
+            );
+            std::process::exit(0);
+        } else {
+            exit_with_usage(&format!("unknown argument: {}", arg));
+        }
+    }
+    config_path
+}
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{}\n{}", error, USAGE);
+    std::process::exit(2);
+}
@@ -0,0 +1,262 @@
+use crate::config::{Config, MultiplayerSettings, NetGameMode, NetRole};
+use crate::engine::{EngineFamily, engine_name};
+use crate::launch::{build_cmd, build_companion_cmd};
+use std::process::Command;
+
+/// Address the local test players connect to.
+const LOCALHOST: &str = "127.0.0.1";
+
+impl NetRole {
+    pub const ALL: [NetRole; 2] = [NetRole::Host, NetRole::Join];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NetRole::Host => "Host",
+            NetRole::Join => "Join",
+        }
+    }
+}
+
+impl NetGameMode {
+    pub const ALL: [NetGameMode; 3] = [
+        NetGameMode::Coop,
+        NetGameMode::Deathmatch,
+        NetGameMode::AltDeath,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NetGameMode::Coop => "Cooperative",
+            NetGameMode::Deathmatch => "Deathmatch",
+            NetGameMode::AltDeath => "AltDeath",
+        }
+    }
+}
+
+/// How an engine hosts games.
+enum Hosting {
+    /// The host player's game is the server.
+    Peer,
+    /// A server process the host joins like the other players: a program shipped with
+    /// the engine, or the engine itself when None.
+    Server {
+        program: Option<&'static str>,
+        with_files: bool,
+    },
+}
+
+/// Netplay conventions of the engines supporting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetEngine {
+    /// Chocolate Doom and Crispy Doom: `-server`, `-connect`.
+    Chocolate,
+    /// GZDoom and other ZDoom ports: peer-to-peer `-host`, `-join`.
+    ZDoom,
+    /// Client/server, hosting runs `zandronum -host` as the server: `-connect`.
+    Zandronum,
+    /// Client/server, with the `odasrv` server: `-connect`.
+    Odamex,
+    /// PrBoom+, with `prboom-plus-game-server`: `-net`.
+    PrBoom,
+}
+
+impl NetEngine {
+    pub fn of(engine_path: &str) -> Option<Self> {
+        let name = engine_name(engine_path);
+        match EngineFamily::of(engine_path) {
+            EngineFamily::Chocolate if !name.contains("doomretro") => Some(NetEngine::Chocolate),
+            EngineFamily::ZDoom if name.contains("zandronum") => Some(NetEngine::Zandronum),
+            EngineFamily::ZDoom => Some(NetEngine::ZDoom),
+            EngineFamily::Odamex => Some(NetEngine::Odamex),
+            EngineFamily::Boom if name.contains("prboom") || name.contains("glboom") => {
+                Some(NetEngine::PrBoom)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            NetEngine::Chocolate => 2342,
+            NetEngine::ZDoom => 5029,
+            NetEngine::Zandronum | NetEngine::Odamex => 10666,
+            NetEngine::PrBoom => 5030,
+        }
+    }
+
+    /// Most players a game of the engine can have.
+    pub fn max_players(self) -> u8 {
+        match self {
+            NetEngine::Chocolate | NetEngine::PrBoom => 4,
+            NetEngine::ZDoom => 8,
+            NetEngine::Zandronum => 64,
+            NetEngine::Odamex => 255,
+        }
+    }
+
+    pub fn supports_timelimit(self) -> bool {
+        self != NetEngine::PrBoom
+    }
+
+    pub fn supports_fraglimit(self) -> bool {
+        !matches!(self, NetEngine::Chocolate | NetEngine::PrBoom)
+    }
+
+    fn hosting(self, engine_path: &str) -> Hosting {
+        match self {
+            NetEngine::Chocolate | NetEngine::ZDoom => Hosting::Peer,
+            NetEngine::Zandronum => Hosting::Server {
+                program: None,
+                with_files: true,
+            },
+            NetEngine::Odamex => Hosting::Server {
+                program: Some("odasrv"),
+                with_files: true,
+            },
+            // The PrBoom+ server only takes its own options, players load the files.
+            NetEngine::PrBoom => Hosting::Server {
+                program: Some(if engine_name(engine_path).contains("glboom") {
+                    "glboom-plus-game-server"
+                } else {
+                    "prboom-plus-game-server"
+                }),
+                with_files: false,
+            },
+        }
+    }
+
+    /// Arguments of the hosting game, or of the server for client/server engines.
+    fn host_args(self, settings: &MultiplayerSettings) -> Vec<String> {
+        let players = settings.players.to_string();
+        let mut args: Vec<String> = match self {
+            NetEngine::Chocolate => vec!["-server".into(), "-nodes".into(), players],
+            NetEngine::ZDoom => vec!["-host".into(), players],
+            NetEngine::Zandronum => vec!["-host".into(), "+sv_maxplayers".into(), players],
+            NetEngine::Odamex => vec!["+sv_maxplayers".into(), players],
+            NetEngine::PrBoom => vec!["-N".into(), players],
+        };
+        if settings.port != 0 {
+            let port_arg = if self == NetEngine::PrBoom {
+                "-p"
+            } else {
+                "-port"
+            };
+            args.extend([port_arg.into(), settings.port.to_string()]);
+        }
+        let mode_args: &[&str] = match (self, settings.mode) {
+            (_, NetGameMode::Coop) => &[],
+            (NetEngine::Odamex, NetGameMode::Deathmatch) => &["+sv_gametype", "1"],
+            (NetEngine::Odamex, NetGameMode::AltDeath) => &[
+                "+sv_gametype",
+                "1",
+                "+sv_itemsrespawn",
+                "1",
+                "+sv_weaponstay",
+                "0",
+            ],
+            (NetEngine::PrBoom, NetGameMode::Deathmatch) => &["-d"],
+            (NetEngine::PrBoom, NetGameMode::AltDeath) => &["-a"],
+            (_, NetGameMode::Deathmatch) => &["-deathmatch"],
+            (_, NetGameMode::AltDeath) => &["-altdeath"],
+        };
+        args.extend(mode_args.iter().map(|arg| arg.to_string()));
+        let (timelimit_arg, fraglimit_arg) = match self {
+            NetEngine::Chocolate => ("-timer", ""),
+            NetEngine::ZDoom | NetEngine::Zandronum => ("+timelimit", "+fraglimit"),
+            NetEngine::Odamex => ("+sv_timelimit", "+sv_fraglimit"),
+            NetEngine::PrBoom => ("", ""),
+        };
+        if settings.timelimit != 0 && self.supports_timelimit() {
+            args.extend([timelimit_arg.into(), settings.timelimit.to_string()]);
+        }
+        if settings.fraglimit != 0 && self.supports_fraglimit() {
+            args.extend([fraglimit_arg.into(), settings.fraglimit.to_string()]);
+        }
+        args
+    }
+
+    /// Arguments of a player joining the server at `address`.
+    fn join_args(self, address: &str) -> Vec<String> {
+        let arg = match self {
+            NetEngine::Chocolate | NetEngine::Zandronum | NetEngine::Odamex => "-connect",
+            NetEngine::ZDoom => "-join",
+            NetEngine::PrBoom => "-net",
+        };
+        vec![arg.to_string(), address.to_string()]
+    }
+}
+
+/// Builds the processes of the active profile's multiplayer game, in start order, with
+/// the player's own game last: the server of client/server engines, then the players.
+/// `local_players` starts that many players on this computer for testing, hosting
+/// regardless of the profile's role. Empty if the profile or engine can't play online.
+pub fn build_multiplayer_cmds(cfg: &Config, local_players: Option<u8>) -> Vec<Command> {
+    let tab = cfg.get_active_tab();
+    let Some(engine_path) = tab.engine_path.as_deref() else {
+        return Vec::new();
+    };
+    let Some(engine) = NetEngine::of(engine_path) else {
+        return Vec::new();
+    };
+    let mut settings = tab.multiplayer.clone();
+    if let Some(players) = local_players {
+        settings.role = NetRole::Host;
+        settings.players = players;
+    }
+    settings.players = settings.players.clamp(2, engine.max_players());
+    let port = match settings.port {
+        0 => engine.default_port(),
+        port => port,
+    };
+
+    let mut cmds = Vec::new();
+    let (address, players_to_join) = match settings.role {
+        NetRole::Join => (join_address(&settings.address, settings.port), 1),
+        NetRole::Host => {
+            let hosting = engine.hosting(engine_path);
+            let host = match hosting {
+                Hosting::Peer | Hosting::Server { program: None, .. } => build_cmd(cfg),
+                Hosting::Server {
+                    program: Some(program),
+                    with_files,
+                } => build_companion_cmd(cfg, program, with_files),
+            };
+            let Some(mut host) = host else {
+                return Vec::new();
+            };
+            host.args(engine.host_args(&settings));
+            cmds.push(host);
+            let players = if local_players.is_some() {
+                settings.players
+            } else {
+                1
+            };
+            let address = format!("{}:{}", LOCALHOST, port);
+            match hosting {
+                Hosting::Peer => (address, players.saturating_sub(1)),
+                Hosting::Server { .. } => (address, players),
+            }
+        }
+    };
+    if address.is_empty() {
+        return Vec::new();
+    }
+    for _ in 0..players_to_join {
+        let Some(mut cmd) = build_cmd(cfg) else {
+            return Vec::new();
+        };
+        cmd.args(engine.join_args(&address));
+        cmds.push(cmd);
+    }
+    cmds
+}
+
+/// Address to join, with the port appended unless it's the default or already given.
+fn join_address(address: &str, port: u16) -> String {
+    let address = address.trim();
+    if address.is_empty() || port == 0 || address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, port)
+    }
+}
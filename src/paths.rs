@@ -0,0 +1,136 @@
+use crate::config::{Config, RelativePathBase};
+use crate::dirs::{cache_dir, config_dir, data_dir, executable_dir};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static VARIABLE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Names of the built-in directory variables, which user-defined roots can't take.
+pub const BUILTIN_VARIABLES: [&str; 4] = ["cache", "config", "data", "exe"];
+
+/// Expands path variables and relative paths of profile paths.
+///
+/// Variables are written as `$NAME` or `${NAME}` and looked up in the built-in `cache`,
+/// `config`, `data` and `exe` directories first, then in the user-defined roots, then in
+/// the environment (e.g. `$HOME`, `$DOOMWADDIR`). A leading `~` stands for the home
+/// directory. Paths that are still relative after expansion are resolved against the
+/// config or the executable directory.
+#[derive(Debug, Clone)]
+pub struct PathResolver {
+    variables: BTreeMap<String, String>,
+    base_dir: PathBuf,
+}
+
+impl PathResolver {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            variables: cfg.path_variables.clone(),
+            base_dir: match cfg.relative_path_base {
+                RelativePathBase::ConfigDir => config_dir(),
+                RelativePathBase::ExecutableDir => executable_dir(),
+            },
+        }
+    }
+
+    pub fn resolve(&self, raw: &str) -> PathBuf {
+        self.resolve_with(raw, true)
+    }
+
+    /// Resolves a path, optionally without user-defined roots. Roots themselves are
+    /// resolved without them, so that they can't refer to each other recursively.
+    fn resolve_with(&self, raw: &str, user_roots: bool) -> PathBuf {
+        let expanded = self.expand_variables(raw, user_roots);
+        let expanded = match expanded.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+                format!("{}{}", self.lookup("HOME", false).unwrap_or_default(), rest)
+            }
+            _ => expanded,
+        };
+        let path = PathBuf::from(expanded);
+        if path.is_relative() {
+            self.base_dir.join(path)
+        } else {
+            path
+        }
+    }
+
+    pub fn resolve_string(&self, raw: &str) -> String {
+        self.resolve(raw).to_string_lossy().to_string()
+    }
+
+    /// Expands variables in a string that isn't necessarily a path, like an argument.
+    pub fn expand(&self, raw: &str) -> String {
+        self.expand_variables(raw, true)
+    }
+
+    /// Resolves a program to run. Bare names are kept as they are to be looked up in
+    /// `PATH`.
+    pub fn resolve_program(&self, raw: &str) -> String {
+        let expanded = self.expand(raw);
+        if expanded.contains(['/', '\\']) || expanded.starts_with('~') {
+            self.resolve_string(raw)
+        } else {
+            expanded
+        }
+    }
+
+    /// Rewrites an absolute path to use the longest matching user-defined root, if any.
+    pub fn contract(&self, path: String) -> String {
+        let best = self
+            .variables
+            .iter()
+            .filter(|(name, value)| {
+                !value.is_empty() && !BUILTIN_VARIABLES.contains(&name.as_str())
+            })
+            .filter_map(|(name, value)| {
+                let rest = Path::new(&path)
+                    .strip_prefix(self.resolve_with(value, false))
+                    .ok()?;
+                Some((name, value.len(), rest.to_string_lossy().to_string()))
+            })
+            .max_by_key(|(_, len, _)| *len);
+        match best {
+            Some((name, _, rest)) if rest.is_empty() => format!("${{{}}}", name),
+            Some((name, _, rest)) => format!("${{{}}}/{}", name, rest),
+            None => path,
+        }
+    }
+
+    fn expand_variables(&self, raw: &str, user_roots: bool) -> String {
+        let regex = VARIABLE_REGEX.get_or_init(|| {
+            Regex::new(r"\$\{([A-Za-z0-9_]+)\}|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+        });
+        regex
+            .replace_all(raw, |caps: &regex::Captures| {
+                let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+                self.lookup(name, user_roots)
+                    .unwrap_or_else(|| caps[0].to_owned())
+            })
+            .to_string()
+    }
+
+    fn lookup(&self, name: &str, user_roots: bool) -> Option<String> {
+        // Built-ins come first, so that a root can't redirect the launcher's own files.
+        let builtin = match name {
+            "cache" => Some(cache_dir()),
+            "config" => Some(config_dir()),
+            "data" => Some(data_dir()),
+            "exe" => Some(executable_dir()),
+            _ => None,
+        };
+        if let Some(dir) = builtin {
+            return Some(dir.to_string_lossy().to_string());
+        }
+        if let Some(value) = self.variables.get(name).filter(|_| user_roots) {
+            // Roots may themselves use environment variables, e.g. `$HOME/wads`.
+            return Some(
+                self.resolve_with(value, false)
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        std::env::var(name).ok()
+    }
+}
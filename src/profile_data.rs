@@ -0,0 +1,74 @@
+use crate::config::TabConfig;
+use crate::engine::{EngineFamily, engine_name};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SAVES_DIR: &str = "saves";
+const SCREENSHOTS_DIR: &str = "screenshots";
+const CONFIG_DIR: &str = "config";
+
+/// Raw path of the folder holding the profile's isolated data, a folder of its own unless
+/// set by the user.
+pub fn profile_data_dir(tab: &TabConfig) -> String {
+    tab.isolation
+        .dir
+        .clone()
+        .unwrap_or_else(|| format!("${{data}}/profiles/{}", tab.id))
+}
+
+/// Folder the profile's savegames are kept in when isolated.
+pub fn profile_saves_dir(dir: &Path) -> PathBuf {
+    dir.join(SAVES_DIR)
+}
+
+/// Returns true if any engine data of the profile is kept in its folder.
+pub fn is_isolated(tab: &TabConfig) -> bool {
+    let isolation = &tab.isolation;
+    isolation.saves || isolation.screenshots || isolation.config
+}
+
+/// Engine arguments pointing the enabled kinds of data at the profile's folder `dir`,
+/// for the ones the engine supports. Config files are named after the engine, so
+/// switching engines doesn't mix their settings.
+pub fn profile_data_args(tab: &TabConfig, dir: &Path) -> Vec<(&'static str, PathBuf)> {
+    let Some(engine_path) = tab.engine_path.as_deref() else {
+        return Vec::new();
+    };
+    let family = EngineFamily::of(engine_path);
+    let isolation = &tab.isolation;
+    let mut args = Vec::new();
+    if isolation.saves
+        && let Some(arg) = family.savedir_arg()
+    {
+        args.push((arg, profile_saves_dir(dir)));
+    }
+    if isolation.screenshots
+        && let Some(arg) = family.shotdir_arg()
+    {
+        args.push((arg, dir.join(SCREENSHOTS_DIR)));
+    }
+    if isolation.config {
+        let name = engine_name(engine_path);
+        for (arg, ext) in family.config_args() {
+            let file = format!("{}.{}", name, ext);
+            args.push((*arg, dir.join(CONFIG_DIR).join(file)));
+        }
+    }
+    args
+}
+
+/// Creates the folders of the enabled kinds of data, engines don't create them.
+pub fn create_profile_data_dirs(tab: &TabConfig, dir: &Path) -> io::Result<()> {
+    let isolation = &tab.isolation;
+    for (enabled, name) in [
+        (isolation.saves, SAVES_DIR),
+        (isolation.screenshots, SCREENSHOTS_DIR),
+        (isolation.config, CONFIG_DIR),
+    ] {
+        if enabled {
+            fs::create_dir_all(dir.join(name))?;
+        }
+    }
+    Ok(())
+}
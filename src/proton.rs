@@ -0,0 +1,181 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+static PROTON_RUNNERS: OnceLock<Mutex<Vec<ProtonRunner>>> = OnceLock::new();
+static VDF_PAIR_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Proton build usable as umu-run's `PROTONPATH`.
+pub struct ProtonRunner {
+    pub name: String,
+    pub path: String,
+    /// Where the runner was found, e.g. "Steam" or "Heroic".
+    pub source: &'static str,
+}
+
+/// Returns the cached runners, scanning on first use.
+pub fn installed_proton_runners(user_dirs: &[String]) -> MutexGuard<'static, Vec<ProtonRunner>> {
+    PROTON_RUNNERS
+        .get_or_init(|| Mutex::new(find_installed_proton_runners(user_dirs)))
+        .lock()
+        .unwrap()
+}
+
+pub fn refresh_installed_proton_runners(user_dirs: &[String]) {
+    let cache = PROTON_RUNNERS.get_or_init(|| Mutex::new(Vec::new()));
+    if let Ok(mut runners) = cache.lock() {
+        *runners = find_installed_proton_runners(user_dirs);
+    }
+}
+
+/// Scans Steam (native and Flatpak, all library folders), Lutris, Heroic and the given
+/// folders. A runner found in several places is listed once.
+fn find_installed_proton_runners(user_dirs: &[String]) -> Vec<ProtonRunner> {
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+
+    let mut runners = Vec::new();
+
+    for dir in user_dirs {
+        let dir = Path::new(dir);
+        if is_proton_runner(dir) {
+            push_runner(&mut runners, dir, "User");
+        } else {
+            scan_runners(&mut runners, dir, "User", |_| true);
+        }
+    }
+
+    let steam_roots = [
+        (home.join(".steam/steam"), "Steam"),
+        (home.join(".steam/root"), "Steam"),
+        (home.join(".local/share/Steam"), "Steam"),
+        (
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            "Flatpak Steam",
+        ),
+        (
+            home.join(".var/app/com.valvesoftware.Steam/data/Steam"),
+            "Flatpak Steam",
+        ),
+    ];
+    for (root, source) in &steam_roots {
+        scan_runners(
+            &mut runners,
+            &root.join("compatibilitytools.d"),
+            source,
+            |_| true,
+        );
+        for library in steam_library_folders(root) {
+            scan_runners(
+                &mut runners,
+                &library.join("steamapps/common"),
+                source,
+                |name| name.contains("Proton"),
+            );
+        }
+    }
+    scan_runners(
+        &mut runners,
+        Path::new("/usr/share/steam/compatibilitytools.d"),
+        "Steam",
+        |_| true,
+    );
+
+    let tool_dirs = [
+        (home.join(".local/share/lutris/runners/proton"), "Lutris"),
+        (home.join(".config/heroic/tools/proton"), "Heroic"),
+        (
+            home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic/tools/proton"),
+            "Heroic",
+        ),
+    ];
+    for (dir, source) in &tool_dirs {
+        scan_runners(&mut runners, dir, source, |_| true);
+    }
+
+    // Steam roots are often symlinks to each other.
+    let mut seen = HashSet::new();
+    runners.retain(|runner| {
+        let path = fs::canonicalize(&runner.path).unwrap_or_else(|_| PathBuf::from(&runner.path));
+        seen.insert(path)
+    });
+    runners
+}
+
+/// Adds the runners that are subdirectories of `dir` and whose names pass the filter.
+fn scan_runners(
+    runners: &mut Vec<ProtonRunner>,
+    dir: &Path,
+    source: &'static str,
+    filter: impl Fn(&str) -> bool,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| filter(&name.to_string_lossy()))
+        })
+        .filter(|path| is_proton_runner(path))
+        .collect();
+    paths.sort();
+    for path in paths {
+        push_runner(runners, &path, source);
+    }
+}
+
+fn push_runner(runners: &mut Vec<ProtonRunner>, path: &Path, source: &'static str) {
+    runners.push(ProtonRunner {
+        name: runner_display_name(path),
+        path: path.to_string_lossy().to_string(),
+        source,
+    });
+}
+
+/// Proton builds have a `proton` launcher script at their top level.
+fn is_proton_runner(dir: &Path) -> bool {
+    dir.join("proton").is_file()
+}
+
+/// Names the runner after its `compatibilitytool.vdf` display name, its `version` file
+/// (`<timestamp> <name>`), or its directory, in that order.
+fn runner_display_name(dir: &Path) -> String {
+    if let Ok(text) = fs::read_to_string(dir.join("compatibilitytool.vdf"))
+        && let Some(name) = vdf_values(&text, "display_name").into_iter().next()
+    {
+        return name;
+    }
+    if let Ok(text) = fs::read_to_string(dir.join("version"))
+        && let Some(name) = text.split_whitespace().nth(1)
+    {
+        return name.to_owned();
+    }
+    dir.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Reads the library folders of a Steam installation, including itself.
+fn steam_library_folders(root: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![root.to_path_buf()];
+    if let Ok(text) = fs::read_to_string(root.join("steamapps/libraryfolders.vdf")) {
+        folders.extend(vdf_values(&text, "path").into_iter().map(PathBuf::from));
+    }
+    folders
+}
+
+/// Returns the values of all `"key" "value"` pairs with the given key, at any depth.
+/// That's enough of Valve's KeyValues format for the files read here.
+fn vdf_values(text: &str, key: &str) -> Vec<String> {
+    let regex =
+        VDF_PAIR_REGEX.get_or_init(|| Regex::new(r#""([^"]*)"\s+"((?:[^"\\]|\\.)*)""#).unwrap());
+    regex
+        .captures_iter(text)
+        .filter(|caps| caps[1].eq_ignore_ascii_case(key))
+        .map(|caps| caps[2].replace("\\\\", "\\"))
+        .collect()
+}
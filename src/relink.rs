@@ -0,0 +1,282 @@
+use crate::archive::clear_archive_checks;
+use crate::config::{Config, FileStamp, TabConfig};
+use crate::files::hash_file;
+use crate::flatpak::flatpak_app_id;
+use crate::paths::PathResolver;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::UNIX_EPOCH;
+use uuid::Uuid;
+
+/// Outcome of relinking, reported to the user.
+pub struct RelinkReport {
+    pub relinked: usize,
+    pub profiles: usize,
+    pub still_missing: Vec<String>,
+}
+
+/// Returns the paths used by the profile: engine, IWAD, inputs and source archives.
+/// Files extracted from archives are left out, they are restored from their archive, and
+/// so are Flatpak engines, which aren't files.
+pub fn profile_paths(tab: &TabConfig) -> Vec<&str> {
+    let mut paths: Vec<&str> = tab
+        .engine_path
+        .iter()
+        .filter(|engine| flatpak_app_id(engine).is_none())
+        .chain(tab.iwad_path.iter())
+        .map(String::as_str)
+        .collect();
+    paths.extend(
+        tab.input_paths
+            .iter()
+            .filter(|p| tab.archive_for_input(p).is_none())
+            .map(String::as_str),
+    );
+    paths.extend(tab.archives.iter().map(|a| a.path.as_str()));
+    paths
+}
+
+/// Returns the profile paths pointing to nonexistent files.
+pub fn missing_paths(tab: &TabConfig, resolver: &PathResolver) -> Vec<String> {
+    profile_paths(tab)
+        .into_iter()
+        .filter(|p| !resolver.resolve(p).exists())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Content hashes of profile files computed on a worker thread, by path.
+pub struct FileHashes {
+    pub profile_id: Uuid,
+    hashes: Vec<(String, String, FileStamp)>,
+}
+
+/// Starts recording content hashes of the profile files, so they can be found by content
+/// if renamed. Files are only hashed if new or changed since their hash was recorded,
+/// on a worker thread whose results are applied with `apply_file_hashes`. Hashes of
+/// files the profile no longer uses are dropped right away. Returns None if no file
+/// needs hashing.
+pub fn start_file_hashing(
+    tab: &mut TabConfig,
+    resolver: &PathResolver,
+) -> Option<Receiver<FileHashes>> {
+    let used: Vec<String> = profile_paths(tab).into_iter().map(str::to_owned).collect();
+    tab.file_hashes.retain(|path, _| used.contains(path));
+    tab.file_stamps.retain(|path, _| used.contains(path));
+    let stale: Vec<(String, PathBuf, FileStamp)> = used
+        .into_iter()
+        .filter_map(|path| {
+            let resolved = resolver.resolve(&path);
+            let stamp = file_stamp(&resolved)?;
+            let unchanged =
+                tab.file_hashes.contains_key(&path) && tab.file_stamps.get(&path) == Some(&stamp);
+            (!unchanged).then_some((path, resolved, stamp))
+        })
+        .collect();
+    if stale.is_empty() {
+        return None;
+    }
+    let profile_id = tab.id;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let hashes = stale
+            .into_iter()
+            .filter_map(|(path, resolved, stamp)| Some((path, hash_file(&resolved).ok()?, stamp)))
+            .collect();
+        let _ = sender.send(FileHashes { profile_id, hashes });
+    });
+    Some(receiver)
+}
+
+/// Records hashes computed by `start_file_hashing`, for the files the profile still uses.
+pub fn apply_file_hashes(tab: &mut TabConfig, hashes: FileHashes) {
+    let used: Vec<String> = profile_paths(tab).into_iter().map(str::to_owned).collect();
+    for (path, hash, stamp) in hashes.hashes {
+        if used.contains(&path) {
+            tab.file_hashes.insert(path.clone(), hash);
+            tab.file_stamps.insert(path, stamp);
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(FileStamp {
+        size: metadata.len(),
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+    })
+}
+
+/// Searches the library folders for the missing files of every profile, by file name
+/// and, when a content hash is known, by hash. Found files are relinked in all profiles.
+pub fn relink_missing_files(cfg: &mut Config) -> RelinkReport {
+    let resolver = PathResolver::new(cfg);
+    // Content hash and, when known, size of each missing file.
+    let mut missing: HashMap<String, Option<(String, Option<u64>)>> = HashMap::new();
+    for tab in cfg.tabs.iter().chain(cfg.library.iter()) {
+        for path in missing_paths(tab, &resolver) {
+            let hash = tab.file_hashes.get(&path).map(|hash| {
+                let size = tab.file_stamps.get(&path).map(|stamp| stamp.size);
+                (hash.clone(), size)
+            });
+            let entry = missing.entry(path).or_default();
+            if entry.is_none() {
+                *entry = hash;
+            }
+        }
+    }
+
+    let mut index = LibraryIndex::new(cfg, &resolver);
+    let mut replacements: HashMap<String, String> = HashMap::new();
+    let mut still_missing = Vec::new();
+    for (old_path, hash) in &missing {
+        let name = file_name_key(&resolver.resolve(old_path));
+        let found = match hash {
+            Some((hash, size)) => index.find(&name, *size, hash),
+            None => index.unique_by_name(&name).cloned(),
+        };
+        match found {
+            Some(new_path) => {
+                let new_path = resolver.contract(new_path.to_string_lossy().to_string());
+                replacements.insert(old_path.clone(), new_path);
+            }
+            None => still_missing.push(old_path.clone()),
+        }
+    }
+
+    let mut profiles = 0;
+    for tab in cfg.tabs.iter_mut().chain(cfg.library.iter_mut()) {
+        if replace_paths(tab, &replacements) {
+            profiles += 1;
+        }
+    }
+    still_missing.sort();
+    if !replacements.is_empty() {
+        clear_archive_checks();
+    }
+
+    RelinkReport {
+        relinked: replacements.len(),
+        profiles,
+        still_missing,
+    }
+}
+
+/// Replaces old paths of the profile with new ones. Returns true if anything changed.
+fn replace_paths(tab: &mut TabConfig, replacements: &HashMap<String, String>) -> bool {
+    let mut changed = false;
+    let mut replace = |path: &mut String| {
+        if let Some(new_path) = replacements.get(path) {
+            *path = new_path.clone();
+            changed = true;
+        }
+    };
+    tab.engine_path.iter_mut().for_each(&mut replace);
+    tab.iwad_path.iter_mut().for_each(&mut replace);
+    tab.input_paths.iter_mut().for_each(&mut replace);
+    tab.archives.iter_mut().for_each(|a| replace(&mut a.path));
+    for (old_path, new_path) in replacements {
+        if let Some(hash) = tab.file_hashes.remove(old_path) {
+            tab.file_hashes.insert(new_path.clone(), hash);
+        }
+        if let Some(stamp) = tab.file_stamps.remove(old_path) {
+            tab.file_stamps.insert(new_path.clone(), stamp);
+        }
+    }
+    changed
+}
+
+/// Files in the library folders, indexed by lowercase file name. Content hashes are
+/// computed on demand and cached.
+pub struct LibraryIndex {
+    by_name: HashMap<String, Vec<PathBuf>>,
+    hashes: HashMap<PathBuf, Option<String>>,
+}
+
+impl LibraryIndex {
+    pub fn new(cfg: &Config, resolver: &PathResolver) -> Self {
+        let mut by_name = HashMap::new();
+        for dir in &cfg.library_dirs {
+            index_files(&resolver.resolve(dir), &mut by_name);
+        }
+        Self {
+            by_name,
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// Finds a file with the given contents, preferring the given file name. The file may
+    /// also have been renamed, so files with the same extension are compared too. When the
+    /// size is known, only files of that size are hashed.
+    pub fn find(&mut self, file_name: &str, size: Option<u64>, hash: &str) -> Option<PathBuf> {
+        let name = file_name.to_lowercase();
+        let extension = extension_key(Path::new(&name));
+        let same_name = self.by_name.get(&name).into_iter().flatten();
+        let same_extension = self
+            .by_name
+            .values()
+            .flatten()
+            .filter(|p| extension_key(p) == extension);
+        for path in same_name.chain(same_extension) {
+            if let Some(size) = size
+                && fs::metadata(path).map(|m| m.len()).ok() != Some(size)
+            {
+                continue;
+            }
+            let path_hash = self
+                .hashes
+                .entry(path.clone())
+                .or_insert_with(|| hash_file(path).ok());
+            if path_hash.as_deref() == Some(hash) {
+                return Some(path.clone());
+            }
+        }
+        None
+    }
+
+    /// Returns the file with the given name if there is exactly one.
+    pub fn unique_by_name(&self, file_name: &str) -> Option<&PathBuf> {
+        match self.by_name.get(&file_name.to_lowercase())?.as_slice() {
+            [path] => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns any file with the given name, regardless of its contents.
+    pub fn any_by_name(&self, file_name: &str) -> Option<&PathBuf> {
+        self.by_name.get(&file_name.to_lowercase())?.first()
+    }
+}
+
+fn index_files(dir: &Path, by_name: &mut HashMap<String, Vec<PathBuf>>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            index_files(&path, by_name);
+        } else {
+            by_name.entry(file_name_key(&path)).or_default().push(path);
+        }
+    }
+}
+
+fn file_name_key(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
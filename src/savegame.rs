@@ -0,0 +1,266 @@
+use crate::config::Config;
+use crate::demo::DemoGame;
+use crate::engine::EngineFamily;
+use crate::launch::{build_cmd, engine_file_arg};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Description and version strings at the start of `.dsg` files.
+const SAVESTRINGSIZE: usize = 24;
+const VERSIONSIZE: usize = 16;
+/// Checksum of the loaded lumps after the version in Boom-based `.dsg` files.
+const CHECKSUMSIZE: usize = 8;
+
+/// Savegame found in a profile's save folder.
+pub struct SaveGame {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    /// Slot from the trailing number of the file name, e.g. 3 for `doomsav3.dsg`.
+    pub slot: Option<u32>,
+}
+
+/// Metadata read from a savegame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveInfo {
+    /// Description typed when saving.
+    pub description: String,
+    /// Engine or game version that wrote the save, e.g. "Doom 1.9" or "PrBoom 2.6".
+    pub format: String,
+    /// Map as `ExMy`/`MAPxx`, None when the format's map can't be trusted.
+    pub map: Option<String>,
+    /// Save time stored in the file, for formats that have one.
+    pub time: Option<String>,
+    /// Screenshot taken when saving, for formats that embed one.
+    pub thumbnail: Option<Arc<Thumbnail>>,
+}
+
+/// Decoded RGBA image.
+#[derive(Debug, PartialEq)]
+pub struct Thumbnail {
+    pub size: [usize; 2],
+    pub rgba: Vec<u8>,
+}
+
+/// Lists the `.dsg` and `.zds` savegames of a folder, newest first.
+pub fn list_savegames(dir: &Path) -> Vec<SaveGame> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut saves: Vec<SaveGame> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("dsg") || ext.eq_ignore_ascii_case("zds")
+            })
+        })
+        .map(|path| SaveGame {
+            modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+            slot: save_slot(&path),
+            path,
+        })
+        .collect();
+    saves.sort_by(|a, b| b.modified.cmp(&a.modified).then(a.path.cmp(&b.path)));
+    saves
+}
+
+/// Slot from the trailing number of a savegame's file name.
+fn save_slot(path: &Path) -> Option<u32> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+type SaveInfoResult = Result<SaveInfo, String>;
+/// Parsed savegames by path and modification time.
+type SaveInfoCache = HashMap<(PathBuf, Option<SystemTime>), SaveInfoResult>;
+
+static SAVE_INFOS: OnceLock<Mutex<SaveInfoCache>> = OnceLock::new();
+
+/// Reads the metadata of a savegame, cached by path and modification time.
+pub fn read_save_info(save: &SaveGame, game: DemoGame) -> SaveInfoResult {
+    let key = (save.path.clone(), save.modified);
+    let cache = SAVE_INFOS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(info) = cache.lock().unwrap().get(&key) {
+        return info.clone();
+    }
+    let is_zds = save
+        .path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zds"));
+    let info = if is_zds {
+        parse_zds(&save.path)
+    } else {
+        fs::read(&save.path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| parse_dsg(&data, game))
+    };
+    cache.lock().unwrap().insert(key, info.clone());
+    info
+}
+
+/// Parses a vanilla, Chocolate Doom or PrBoom+ `.dsg` header: description, version, then
+/// skill, episode and map. Boom-based versions store a checksum of the loaded lumps, the
+/// NUL-terminated list of WADs and the compatibility level first.
+pub fn parse_dsg(data: &[u8], game: DemoGame) -> SaveInfoResult {
+    let header = data
+        .get(..SAVESTRINGSIZE + VERSIONSIZE)
+        .ok_or("Truncated savegame")?;
+    let description = c_string(&header[..SAVESTRINGSIZE]);
+    let version = c_string(&header[SAVESTRINGSIZE..]);
+    let rest = &data[SAVESTRINGSIZE + VERSIONSIZE..];
+    let (format, settings) = match version.strip_prefix("version ") {
+        Some(number) => {
+            let format = match number.parse::<u32>() {
+                Ok(number) => format!("Doom {}.{}", number / 100, number % 100),
+                Err(_) => format!("Doom {}", number),
+            };
+            (format, rest.get(..3))
+        }
+        None if version.is_empty() => return Err("Unknown savegame format".to_owned()),
+        None => {
+            let wads = rest.get(CHECKSUMSIZE..).unwrap_or_default();
+            // The settings follow the compatibility level after the list's NUL.
+            let settings = wads
+                .iter()
+                .position(|&b| b == 0)
+                .and_then(|end| wads.get(end + 2..end + 5));
+            (version, settings)
+        }
+    };
+    // Skill, episode and map.
+    let settings = settings.ok_or("Truncated savegame")?;
+    let (episode, map) = (settings[1], settings[2]);
+    let map = (map > 0 && map < 100 && episode < 10).then(|| game.map_name(episode, map));
+    Ok(SaveInfo {
+        description,
+        format,
+        map,
+        time: None,
+        thumbnail: None,
+    })
+}
+
+/// Parses a GZDoom `.zds` savegame, a zip with `info.json` and `savepic.png`.
+pub fn parse_zds(path: &Path) -> SaveInfoResult {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    zip.by_name("info.json")
+        .map_err(|e| e.to_string())?
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+    let info: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let field = |name: &str| info.get(name).and_then(Value::as_str).map(str::to_owned);
+
+    let mut png = Vec::new();
+    let thumbnail = zip
+        .by_name("savepic.png")
+        .ok()
+        .and_then(|mut entry| entry.read_to_end(&mut png).ok())
+        .and_then(|_| image::load_from_memory_with_format(&png, image::ImageFormat::Png).ok())
+        .map(|image| {
+            let image = image.to_rgba8();
+            Arc::new(Thumbnail {
+                size: [image.width() as usize, image.height() as usize],
+                rgba: image.into_raw(),
+            })
+        });
+    Ok(SaveInfo {
+        description: field("Title").unwrap_or_default(),
+        format: field("Engine").unwrap_or_else(|| "GZDoom".to_owned()),
+        map: field("Current Map"),
+        time: field("Creation Time"),
+        thumbnail,
+    })
+}
+
+/// Builds the command launching the active profile into a savegame. ZDoom ports load
+/// the file, the others a slot of their save folder.
+pub fn build_loadgame_cmd(cfg: &Config, save: &SaveGame) -> Option<Command> {
+    let tab = cfg.get_active_tab();
+    let family = EngineFamily::of(tab.engine_path.as_deref()?);
+    let value = match family {
+        EngineFamily::ZDoom => engine_file_arg(tab, save.path.clone()),
+        _ => save.slot?.to_string().into(),
+    };
+    let mut cmd = build_cmd(cfg)?;
+    cmd.arg("-loadgame").arg(value);
+    Some(cmd)
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Description and version strings padded to their fixed sizes.
+    fn dsg_header(description: &str, version: &str) -> Vec<u8> {
+        let mut data = description.as_bytes().to_vec();
+        data.resize(SAVESTRINGSIZE, 0);
+        data.extend(version.as_bytes());
+        data.resize(SAVESTRINGSIZE + VERSIONSIZE, 0);
+        data
+    }
+
+    #[test]
+    fn parses_vanilla_savegames() {
+        let mut data = dsg_header("ENTRYWAY", "version 109");
+        // Skill, episode, map.
+        data.extend([3, 1, 1]);
+        let info = parse_dsg(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(info.description, "ENTRYWAY");
+        assert_eq!(info.format, "Doom 1.9");
+        assert_eq!(info.map.as_deref(), Some("MAP01"));
+
+        let info = parse_dsg(&data, DemoGame::Doom).unwrap();
+        assert_eq!(info.map.as_deref(), Some("E1M1"));
+
+        // A map out of range isn't shown.
+        let len = data.len();
+        data[len - 1] = 0;
+        assert_eq!(parse_dsg(&data, DemoGame::Doom2).unwrap().map, None);
+    }
+
+    #[test]
+    fn parses_prboom_savegames() {
+        let mut data = dsg_header("scythe 27", "PrBoom 2.6");
+        data.extend([0xAB; CHECKSUMSIZE]);
+        data.extend(b"doom2.wad\nscythe.wad\0");
+        // Compatibility level, then skill, episode and map.
+        data.extend([17, 3, 1, 27]);
+        let info = parse_dsg(&data, DemoGame::Doom2).unwrap();
+        assert_eq!(info.description, "scythe 27");
+        assert_eq!(info.format, "PrBoom 2.6");
+        assert_eq!(info.map.as_deref(), Some("MAP27"));
+    }
+
+    #[test]
+    fn rejects_truncated_and_unknown_savegames() {
+        assert!(parse_dsg(&[0; 10], DemoGame::Doom2).is_err());
+        assert!(parse_dsg(&dsg_header("save", ""), DemoGame::Doom2).is_err());
+        assert!(parse_dsg(&dsg_header("save", "version 109"), DemoGame::Doom2).is_err());
+        let mut data = dsg_header("save", "PrBoom 2.6");
+        data.extend([0; CHECKSUMSIZE]);
+        data.extend(b"doom2.wad");
+        assert!(parse_dsg(&data, DemoGame::Doom2).is_err());
+    }
+
+    #[test]
+    fn reads_slot_from_file_name() {
+        assert_eq!(save_slot(Path::new("/saves/doomsav3.dsg")), Some(3));
+        assert_eq!(save_slot(Path::new("prbmsav0.dsg")), Some(0));
+        assert_eq!(save_slot(Path::new("hexen12.dsg")), Some(12));
+        assert_eq!(save_slot(Path::new("quicksave.zds")), None);
+    }
+}
@@ -0,0 +1,418 @@
+use crate::config::{RunCategory, SpeedrunSettings, TabConfig};
+use crate::demo::list_demos;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+/// Report dsda-doom writes to its working directory with `-analysis`.
+const ANALYSIS_FILE: &str = "analysis.txt";
+/// Report dsda-doom writes to its working directory with `-levelstat`.
+const LEVELSTAT_FILE: &str = "levelstat.txt";
+
+impl RunCategory {
+    pub const ALL: [RunCategory; 10] = [
+        RunCategory::UvMax,
+        RunCategory::UvSpeed,
+        RunCategory::NmSpeed,
+        RunCategory::Nm100,
+        RunCategory::UvFast,
+        RunCategory::UvRespawn,
+        RunCategory::NoMo,
+        RunCategory::NoMo100,
+        RunCategory::Pacifist,
+        RunCategory::Tyson,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RunCategory::UvMax => "UV-Max",
+            RunCategory::UvSpeed => "UV-Speed",
+            RunCategory::NmSpeed => "NM-Speed",
+            RunCategory::Nm100 => "NM100",
+            RunCategory::UvFast => "UV-Fast",
+            RunCategory::UvRespawn => "UV-Respawn",
+            RunCategory::NoMo => "NoMo",
+            RunCategory::NoMo100 => "NoMo100",
+            RunCategory::Pacifist => "Pacifist",
+            RunCategory::Tyson => "Tyson",
+        }
+    }
+
+    pub fn skill(self) -> u8 {
+        match self {
+            RunCategory::NmSpeed | RunCategory::Nm100 => 5,
+            _ => 4,
+        }
+    }
+
+    /// Game flags the category is played with, besides the skill.
+    pub fn flags(self) -> &'static [&'static str] {
+        match self {
+            RunCategory::UvFast => &["-fast"],
+            RunCategory::UvRespawn => &["-respawn"],
+            RunCategory::NoMo | RunCategory::NoMo100 => &["-nomonsters"],
+            _ => &[],
+        }
+    }
+
+    /// Category name as reported by `-analysis`.
+    pub fn analysis_name(self) -> &'static str {
+        match self {
+            RunCategory::UvMax => "UV Max",
+            RunCategory::UvSpeed => "UV Speed",
+            RunCategory::NmSpeed => "NM Speed",
+            RunCategory::Nm100 => "NM 100S",
+            RunCategory::UvFast => "UV Fast",
+            RunCategory::UvRespawn => "UV Respawn",
+            RunCategory::NoMo => "NoMo",
+            RunCategory::NoMo100 => "NoMo 100S",
+            RunCategory::Pacifist => "Pacifist",
+            RunCategory::Tyson => "Tyson",
+        }
+    }
+
+    /// Letters between map and time in DSDA demo names, e.g. `av01m213.lmp`.
+    fn code(self) -> &'static str {
+        match self {
+            RunCategory::UvMax => "m",
+            RunCategory::UvSpeed => "-",
+            RunCategory::NmSpeed => "n",
+            RunCategory::Nm100 => "s",
+            RunCategory::UvFast => "f",
+            RunCategory::UvRespawn => "r",
+            RunCategory::NoMo => "o",
+            RunCategory::NoMo100 => "os",
+            RunCategory::Pacifist => "p",
+            RunCategory::Tyson => "t",
+        }
+    }
+}
+
+/// dsda-doom arguments of the speedrun mode. The skill and flags of the category only
+/// apply to recordings, demos carry their own.
+pub fn speedrun_args(settings: &SpeedrunSettings, recording: bool) -> Vec<String> {
+    let mut args = Vec::new();
+    if recording {
+        args.push("-skill".to_string());
+        args.push(settings.category.skill().to_string());
+        args.extend(
+            settings
+                .category
+                .flags()
+                .iter()
+                .map(|flag| flag.to_string()),
+        );
+    }
+    // Without strict mode, for recording and continuing TAS demos.
+    if settings.tas {
+        args.push("-tas".to_string());
+    }
+    args.push("-analysis".to_string());
+    args.push("-levelstat".to_string());
+    if settings.export_text_file {
+        args.push("-export_text_file".to_string());
+    }
+    args
+}
+
+/// dsda-doom process started in speedrun mode, whose reports are read once it exits.
+pub struct SpeedrunRun {
+    /// Working directory the reports and recordings are written to.
+    pub dir: PathBuf,
+    pub started: SystemTime,
+}
+
+impl SpeedrunRun {
+    /// Returns the run if the command asks for an analysis.
+    pub fn from_cmd(cmd: &Command) -> Option<Self> {
+        if !cmd.get_args().any(|arg| arg == OsStr::new("-analysis")) {
+            return None;
+        }
+        Some(SpeedrunRun {
+            dir: cmd.get_current_dir()?.to_path_buf(),
+            started: SystemTime::now(),
+        })
+    }
+}
+
+/// Outcome of a speedrun, shown after the engine exits.
+pub struct SpeedrunResult {
+    pub profile: String,
+    pub category: RunCategory,
+    /// Key/value pairs of `analysis.txt`, in file order.
+    pub analysis: Vec<(String, String)>,
+    /// Level name and time of each completed level.
+    pub levels: Vec<(String, String)>,
+    /// Time of the whole run in whole seconds, e.g. `1:02`.
+    pub total: Option<String>,
+    /// Demo recorded by the run, after renaming it.
+    pub demo: Option<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl SpeedrunResult {
+    /// Category detected by the analysis.
+    pub fn detected_category(&self) -> Option<&str> {
+        self.analysis
+            .iter()
+            .find(|(key, _)| key == "category")
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn category_matches(&self) -> bool {
+        self.detected_category() == Some(self.category.analysis_name())
+    }
+}
+
+/// Reads the reports of an exited run and renames the demo it recorded following DSDA
+/// conventions. Returns None if the run wrote no analysis, e.g. when quitting from the
+/// menu before starting a level.
+pub fn finish_speedrun(run: &SpeedrunRun, profile: &TabConfig) -> Option<SpeedrunResult> {
+    let analysis_path = run.dir.join(ANALYSIS_FILE);
+    let written = fs::metadata(&analysis_path)
+        .and_then(|m| m.modified())
+        .ok()?;
+    if written < run.started {
+        return None;
+    }
+    let analysis = parse_analysis(&fs::read_to_string(&analysis_path).ok()?);
+    let levelstat = fs::read_to_string(run.dir.join(LEVELSTAT_FILE)).unwrap_or_default();
+    let mut result = SpeedrunResult {
+        profile: profile.name.clone().unwrap_or_default(),
+        category: profile.speedrun.category,
+        analysis,
+        levels: parse_levelstat(&levelstat),
+        total: parse_levelstat_total(&levelstat),
+        demo: None,
+        error: None,
+    };
+
+    let recorded = list_demos(&run.dir)
+        .into_iter()
+        .find(|demo| demo.modified.is_some_and(|m| m >= run.started));
+    if let Some(recorded) = recorded {
+        match dsda_demo_name(profile, &result.levels, result.total.as_deref()) {
+            Some(name) => {
+                let target = unused_path(&run.dir, &name);
+                match rename_demo(&recorded.path, &target) {
+                    Ok(()) => result.demo = Some(target),
+                    Err(err) => {
+                        result.error = Some(format!("{}: {}", recorded.path.display(), err));
+                        // The text file may have failed after the demo was moved.
+                        let moved = target.exists() && !recorded.path.exists();
+                        result.demo = Some(if moved { target } else { recorded.path });
+                    }
+                }
+            }
+            None => result.demo = Some(recorded.path),
+        }
+    }
+    Some(result)
+}
+
+/// Parses `analysis.txt`, made of `key value` lines.
+pub fn parse_analysis(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(' ')?;
+            Some((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Parses `levelstat.txt` lines like `MAP01 - 0:13.97 (0:13)  K: 9/9  I: 9/9  S: 2/2`.
+pub fn parse_levelstat(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (map, rest) = line.split_once(" - ")?;
+            let time = rest.split_whitespace().next()?;
+            Some((map.trim().to_string(), time.to_string()))
+        })
+        .collect()
+}
+
+/// Total time of the run, in parentheses on the last line of `levelstat.txt`.
+pub fn parse_levelstat_total(text: &str) -> Option<String> {
+    let line = text.lines().rfind(|line| line.contains(" - "))?;
+    let (_, rest) = line.split_once('(')?;
+    let (total, _) = rest.split_once(')')?;
+    Some(total.trim().to_string())
+}
+
+/// DSDA demo name: WAD abbreviation, map number, category code and time in minutes and
+/// seconds, e.g. `av01m213.lmp` or `doom201-037.lmp`. Runs of several levels are named
+/// after the first one with the total time.
+fn dsda_demo_name(
+    profile: &TabConfig,
+    levels: &[(String, String)],
+    total: Option<&str>,
+) -> Option<String> {
+    let wad_path = profile
+        .input_paths
+        .iter()
+        .find(|p| {
+            Path::new(p)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wad"))
+        })
+        .or(profile.iwad_path.as_ref())?;
+    let wad: String = Path::new(wad_path)
+        .file_stem()?
+        .to_string_lossy()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(6)
+        .collect::<String>()
+        .to_lowercase();
+    let (first_map, _) = levels.first()?;
+    let map: String = first_map.chars().filter(|c| c.is_ascii_digit()).collect();
+    let total_secs = parse_time_secs(total?)?;
+    let time = format!("{}{:02}", total_secs / 60, total_secs % 60);
+    Some(format!(
+        "{}{:0>2}{}{}.lmp",
+        wad,
+        map,
+        profile.speedrun.category.code(),
+        time
+    ))
+}
+
+/// Whole seconds of a `m:ss` or `h:mm:ss` time, ignoring fractions of seconds.
+fn parse_time_secs(time: &str) -> Option<u64> {
+    let whole = time.split('.').next()?;
+    whole
+        .split(':')
+        .try_fold(0, |secs, part| Some(secs * 60 + part.parse::<u64>().ok()?))
+}
+
+/// Returns `dir/name`, or `dir/<stem>-<n>.lmp` with the first unused number when taken.
+fn unused_path(dir: &Path, name: &str) -> PathBuf {
+    let stem = name.trim_end_matches(".lmp");
+    let mut path = dir.join(name);
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.lmp", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// Renames a demo along with the text file `-export_text_file` writes next to it.
+fn rename_demo(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::rename(from, to)?;
+    let text_file = from.with_extension("txt");
+    if text_file.exists() {
+        fs::rename(&text_file, to.with_extension("txt"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELSTAT: &str = "MAP01 - 0:13.97 (0:13)  K: 9/9  I: 9/9  S: 2/2
+MAP02 - 0:47.20 (1:00)  K: 20/20  I: 10/12  S: 1/3
+MAP03 - 1:02:03.51 (1:03:03)  K: 60/61  I: 0/0  S: 0/1
+";
+
+    fn profile(category: RunCategory) -> TabConfig {
+        let mut profile = TabConfig {
+            iwad_path: Some("/iwads/DOOM2.WAD".to_owned()),
+            input_paths: vec!["/wads/Alien-Vendetta.wad".to_owned()],
+            ..TabConfig::default()
+        };
+        profile.speedrun.category = category;
+        profile
+    }
+
+    #[test]
+    fn parses_levelstat_lines() {
+        assert_eq!(
+            parse_levelstat(LEVELSTAT),
+            [
+                ("MAP01".to_owned(), "0:13.97".to_owned()),
+                ("MAP02".to_owned(), "0:47.20".to_owned()),
+                ("MAP03".to_owned(), "1:02:03.51".to_owned()),
+            ]
+        );
+        assert!(parse_levelstat("").is_empty());
+    }
+
+    #[test]
+    fn parses_levelstat_total_of_the_last_level() {
+        assert_eq!(parse_levelstat_total(LEVELSTAT).as_deref(), Some("1:03:03"));
+        let single = LEVELSTAT.lines().next().unwrap();
+        assert_eq!(parse_levelstat_total(single).as_deref(), Some("0:13"));
+        assert_eq!(parse_levelstat_total(""), None);
+    }
+
+    #[test]
+    fn parses_times_in_whole_seconds() {
+        assert_eq!(parse_time_secs("0:13.97"), Some(13));
+        assert_eq!(parse_time_secs("2:05"), Some(125));
+        assert_eq!(parse_time_secs("1:03:03"), Some(3783));
+        assert_eq!(parse_time_secs("soon"), None);
+    }
+
+    #[test]
+    fn names_demos_after_wad_map_category_and_time() {
+        let levels = [("MAP01".to_owned(), "2:13.97".to_owned())];
+        let names: Vec<String> = RunCategory::ALL
+            .iter()
+            .map(|category| dsda_demo_name(&profile(*category), &levels, Some("2:13")).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "alienv01m213.lmp",
+                "alienv01-213.lmp",
+                "alienv01n213.lmp",
+                "alienv01s213.lmp",
+                "alienv01f213.lmp",
+                "alienv01r213.lmp",
+                "alienv01o213.lmp",
+                "alienv01os213.lmp",
+                "alienv01p213.lmp",
+                "alienv01t213.lmp",
+            ]
+        );
+
+        // Runs of several levels use the total time, and the IWAD without a PWAD.
+        let mut iwad_only = profile(RunCategory::UvSpeed);
+        iwad_only.input_paths.clear();
+        let levels = parse_levelstat(LEVELSTAT);
+        assert_eq!(
+            dsda_demo_name(&iwad_only, &levels, Some("1:03:03")).as_deref(),
+            Some("doom201-6303.lmp")
+        );
+        assert_eq!(dsda_demo_name(&iwad_only, &levels, None), None);
+    }
+
+    #[test]
+    fn recordings_get_the_category_flags() {
+        let settings = SpeedrunSettings {
+            enabled: true,
+            category: RunCategory::NoMo100,
+            export_text_file: false,
+            tas: true,
+        };
+        assert_eq!(
+            speedrun_args(&settings, true),
+            [
+                "-skill",
+                "4",
+                "-nomonsters",
+                "-tas",
+                "-analysis",
+                "-levelstat"
+            ]
+        );
+        assert_eq!(
+            speedrun_args(&settings, false),
+            ["-tas", "-analysis", "-levelstat"]
+        );
+    }
+}